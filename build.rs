@@ -0,0 +1,223 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2015 AT&T
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// Generates structs from FlexSwitch model descriptions, a directory with one
+// <Object>Members.json per object, as shipped with FlexSwitch, mapping every
+// member to its attributes:
+//
+//     "IntfRef": {"type": "string", "isKey": true, "isArray": false, "default": "", "position": 1}
+//
+// Members with "isDefaultSet": false have no default on the switch and become
+// Options, only sent when set, so a PATCH leaves them alone.
+//
+// The structs the api itself uses, like Port and IPv4Route, always come from
+// the models directory of this crate. With the "models" feature all objects in
+// FLEXSWITCH_MODELS, or in the bundled models when it is unset, become
+// api::models. Objects ending in "State" are read-only structs there, all
+// others also implement api::ConfigObject if they have at least one key member.
+
+extern crate serde_json;
+
+use serde_json::Value;
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+struct Member {
+    name: String,
+    kind: String,
+    is_key: bool,
+    is_array: bool,
+    is_optional: bool,
+    default: String,
+    position: i64,
+}
+
+fn main() {
+    let bundled_models_dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("models");
+    let out_dir = env::var("OUT_DIR").unwrap();
+
+    println!("cargo:rerun-if-changed={}", bundled_models_dir.display());
+    generate_models(&bundled_models_dir, false, &Path::new(&out_dir).join("api_models.rs"));
+
+    if env::var("CARGO_FEATURE_MODELS").is_err() {
+        return;
+    }
+
+    println!("cargo:rerun-if-env-changed=FLEXSWITCH_MODELS");
+    let models_dir = match env::var("FLEXSWITCH_MODELS") {
+        Ok(models_dir) => PathBuf::from(models_dir),
+        Err(_) => {
+            println!("cargo:warning=FLEXSWITCH_MODELS is not set, api::models only has the bundled models");
+            bundled_models_dir
+        }
+    };
+    println!("cargo:rerun-if-changed={}", models_dir.display());
+    generate_models(&models_dir, true, &Path::new(&out_dir).join("models.rs"));
+}
+
+fn generate_models(models_dir: &Path, config_objects: bool, out_file: &Path) {
+    let mut model_files = vec![];
+    for entry in fs::read_dir(models_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
+        if file_name.ends_with("Members.json") {
+            model_files.push((file_name.trim_end_matches("Members.json").to_string(), path.clone()));
+        }
+    }
+    model_files.sort();
+
+    let mut code = String::new();
+    code.push_str("// generated by build.rs from the FlexSwitch models, do not edit\n\n");
+    if config_objects {
+        code.push_str("use super::ConfigObject;\n");
+    }
+    code.push_str("use serde::{Deserialize, Serialize};\n");
+
+    for (object_name, path) in &model_files {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let members = read_members(path);
+        code.push_str(&generate_object(object_name, &members, config_objects));
+    }
+
+    let mut file = File::create(out_file).unwrap();
+    file.write_all(code.as_bytes()).unwrap();
+}
+
+fn read_members(path: &Path) -> Vec<Member> {
+    let mut content = String::new();
+    File::open(path).unwrap().read_to_string(&mut content).unwrap();
+
+    let jsondata: Value = match serde_json::from_str(&content) {
+        Ok(jsondata) => jsondata,
+        Err(err) => panic!("invalid model {}: {}", path.display(), err),
+    };
+
+    let mut result = vec![];
+    for (name, attributes) in jsondata.as_object().unwrap() {
+        result.push(Member {
+            name: name.clone(),
+            kind: attributes.get("type").and_then(|value| value.as_str()).unwrap_or("string").to_string(),
+            is_key: attributes.get("isKey").and_then(|value| value.as_bool()).unwrap_or(false),
+            is_array: attributes.get("isArray").and_then(|value| value.as_bool()).unwrap_or(false),
+            is_optional: attributes.get("isDefaultSet").and_then(|value| value.as_bool()) == Some(false),
+            default: match attributes.get("default") {
+                Some(Value::String(value)) => value.clone(),
+                Some(Value::Null) | None => "".to_string(),
+                Some(value) => value.to_string(),
+            },
+            position: attributes.get("position").and_then(|value| value.as_i64()).unwrap_or(0),
+        });
+    }
+
+    // json objects come back sorted by name, the models keep the FlexSwitch member order in position
+    result.sort_by(|a, b| a.position.cmp(&b.position).then_with(|| a.name.cmp(&b.name)));
+    result
+}
+
+fn generate_object(object_name: &str, members: &[Member], config_object: bool) -> String {
+    let mut code = String::new();
+
+    // members missing from a switch response fall back to the model defaults
+    code.push_str("\n#[derive(Clone, Serialize, Deserialize)]\n#[serde(default)]\n#[allow(non_snake_case)]\n");
+    code.push_str(&format!("pub struct {} {{\n", object_name));
+    for member in members {
+        if is_option(member) {
+            code.push_str("    #[serde(skip_serializing_if = \"Option::is_none\")]\n");
+        }
+        code.push_str(&format!("    pub {}: {},\n", member.name, rust_type(member)));
+    }
+    code.push_str("}\n");
+
+    code.push_str(&format!("\nimpl Default for {} {{\n    fn default() -> {} {{\n        {} {{\n",
+                           object_name,
+                           object_name,
+                           object_name));
+    for member in members {
+        code.push_str(&format!("            {}: {},\n", member.name, rust_default(member)));
+    }
+    code.push_str("        }\n    }\n}\n");
+
+    let keys: Vec<String> = members.iter().filter(|member| member.is_key).map(|member| format!("{:?}", member.name)).collect();
+    if config_object && !object_name.ends_with("State") && !keys.is_empty() {
+        code.push_str(&format!("\nimpl ConfigObject for {} {{\n", object_name));
+        code.push_str(&format!("    fn object_name() -> &'static str {{\n        {:?}\n    }}\n\n", object_name));
+        code.push_str(&format!("    fn key_fields() -> &'static [&'static str] {{\n        &[{}]\n    }}\n",
+                               keys.join(", ")));
+        code.push_str("}\n");
+    }
+
+    code
+}
+
+// anything that is not a go base type refers to another model, like NextHopInfo in IPv4Route
+fn rust_base_type(kind: &str) -> String {
+    match kind {
+        "string" => "String",
+        "bool" => "bool",
+        "int8" => "i8",
+        "int16" => "i16",
+        "int32" | "int" => "i32",
+        "int64" => "i64",
+        "uint8" => "u8",
+        "uint16" => "u16",
+        "uint32" | "uint" => "u32",
+        "uint64" => "u64",
+        "float32" => "f32",
+        "float64" => "f64",
+        other => other,
+    }
+    .to_string()
+}
+
+// keys are always sent, arrays are empty when unset
+fn is_option(member: &Member) -> bool {
+    member.is_optional && !member.is_key && !member.is_array
+}
+
+fn rust_type(member: &Member) -> String {
+    match (member.is_array, is_option(member)) {
+        (true, _) => format!("Vec<{}>", rust_base_type(&member.kind)),
+        (false, true) => format!("Option<{}>", rust_base_type(&member.kind)),
+        (false, false) => rust_base_type(&member.kind),
+    }
+}
+
+fn rust_default(member: &Member) -> String {
+    if member.is_array {
+        return "vec![]".to_string();
+    }
+    if is_option(member) {
+        return "None".to_string();
+    }
+
+    let default = member.default.trim();
+    match rust_base_type(&member.kind).as_str() {
+        "String" => format!("{:?}.to_string()", default),
+        "bool" => (default == "true").to_string(),
+        "f32" | "f64" => format!("{:?}", default.parse::<f64>().unwrap_or(0.0)),
+        "i8" | "i16" | "i32" | "i64" => default.parse::<i64>().unwrap_or(0).to_string(),
+        "u8" | "u16" | "u32" | "u64" => default.parse::<u64>().unwrap_or(0).to_string(),
+        _ => "Default::default()".to_string(),
+    }
+}
@@ -27,6 +27,7 @@ extern crate torc_snaproute_client;
 
 use torc_snaproute_client::api;
 use std::env;
+use std::time::Duration;
 
 fn main() {
     let mut snaproute = "127.0.0.1:8080".to_string();
@@ -40,5 +41,13 @@ fn main() {
 
     println!("Connects to: {}, initalizes switch with config: {}", snaproute, config_file);
 
-    api::reset_and_initalize(&snaproute, &config_file);
+    if let Err(err) = api::reset_and_initalize_when_ready(&snaproute, &config_file, Duration::from_secs(120)) {
+        println!("error {}", err);
+        return;
+    }
+
+    // keep the configuration across reboots
+    if let Err(err) = api::save_config(&snaproute, "") {
+        println!("error {}", err);
+    }
 }
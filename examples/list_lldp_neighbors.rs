@@ -0,0 +1,54 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2015 AT&T
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// Simple example client to list LLDP neighbors per port
+//
+
+extern crate torc_snaproute_client;
+
+use torc_snaproute_client::api;
+use std::env;
+
+fn main() {
+    let mut snaproute = "127.0.0.1:8080".to_string();
+
+    let args: Vec<_> = env::args().collect();
+    if args.len() == 2 {
+        snaproute = args[1].clone();
+    }
+
+    api::set_lldp_global(&snaproute, true);
+
+    let ports_stats = api::get_ports_stats(&snaproute);
+    let neighbors = api::get_lldp_neighbors(&snaproute);
+
+    for port_stat in &ports_stats {
+        for neighbor in neighbors.iter().filter(|neighbor| neighbor.port_id == port_stat.id) {
+            println!("Port {}, Connected {}, Peer {} ({}), Port {}",
+                     port_stat.id,
+                     port_stat.connected,
+                     neighbor.peer_system_name,
+                     neighbor.peer_chassis_id,
+                     neighbor.peer_port_id);
+        }
+    }
+}
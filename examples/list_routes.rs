@@ -38,7 +38,12 @@ fn main() {
 
     let routes = api::get_routes(&snaproute);
     for route in &routes {
-        println!("Route {} --> {}", route.from, route.to);
+        println!("Route {} --> {} ({})", route.from, route.to, route.protocol);
+    }
+
+    let route_counts = api::get_route_counts(&snaproute);
+    for route_count in &route_counts {
+        println!("Protocol {}, Routes {}, ECMP {}", route_count.protocol, route_count.route_count, route_count.ecmp_count);
     }
 
 }
@@ -0,0 +1,53 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2015 AT&T
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// Simple example client to list the routes of several switches concurrently
+//
+
+extern crate torc_snaproute_client;
+extern crate tokio;
+
+use torc_snaproute_client::api::async_client;
+use std::env;
+
+#[tokio::main]
+async fn main() {
+    let mut switches: Vec<String> = env::args().skip(1).collect();
+    if switches.is_empty() {
+        switches.push("127.0.0.1:8080".to_string());
+    }
+
+    let mut tasks = vec![];
+    for snaproute in switches {
+        tasks.push(tokio::spawn(async move {
+            let routes = async_client::get_routes(&snaproute).await;
+            (snaproute, routes)
+        }));
+    }
+
+    for task in tasks {
+        let (snaproute, routes) = task.await.unwrap();
+        for route in &routes {
+            println!("{}: Route {} --> {}", snaproute, route.from, route.to);
+        }
+    }
+}
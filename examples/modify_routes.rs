@@ -41,14 +41,17 @@ fn main() {
         println!("Route {} --> {}", route.from, route.to);
     }
 
-    api::add_route(&snaproute, "172.16.0.12/32", "10.250.3.20");
+    let object_id = api::add_route(&snaproute, "172.16.0.12/32", "10.250.3.20");
 
     let routes = api::get_routes(&snaproute);
     for route in &routes {
         println!("Route {} --> {}", route.from, route.to);
     }
 
-    api::delete_route(&snaproute, "172.16.0.12/32");
+    match object_id {
+        Some(object_id) => api::delete_route_by_id(&snaproute, &object_id),
+        None => api::delete_route(&snaproute, "172.16.0.12/32"),
+    }
 
     let routes = api::get_routes(&snaproute);
     for route in &routes {
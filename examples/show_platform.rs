@@ -0,0 +1,74 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2015 AT&T
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// Simple example client to show system status and platform health
+//
+
+extern crate torc_snaproute_client;
+
+use torc_snaproute_client::api;
+use std::env;
+
+fn main() {
+    let mut snaproute = "127.0.0.1:8080".to_string();
+
+    let args: Vec<_> = env::args().collect();
+    if args.len() == 2 {
+        snaproute = args[1].clone();
+    }
+
+    if let Some(system) = api::get_system_status(&snaproute) {
+        println!("System {}, Ready {}, Uptime {}", system.name, system.ready, system.up_time);
+    }
+
+    if let Some(sw_version) = api::get_sw_version(&snaproute) {
+        println!("FlexSwitch {}", sw_version.flexswitch_version);
+    }
+
+    for daemon in &api::get_daemons(&snaproute) {
+        println!("Daemon {}, State {}, Restarts {}", daemon.name, daemon.state, daemon.restart_count);
+    }
+
+    if let Some(platform) = api::get_platform(&snaproute) {
+        println!("Platform {}, {} {}, Serial {}",
+                 platform.platform_name,
+                 platform.manufacturer,
+                 platform.product_name,
+                 platform.serial_num);
+    }
+
+    for fan in &api::get_fans(&snaproute) {
+        println!("Fan {}, Status {}, Speed {}", fan.fan_id, fan.status, fan.oper_speed);
+    }
+
+    for psu in &api::get_psus(&snaproute) {
+        println!("PSU {}, State {}, Pout {}", psu.psu_id, psu.admin_state, psu.pout);
+    }
+
+    for sensor in &api::get_temperature_sensors(&snaproute) {
+        println!("Temperature {}, {} (warning {}, alarm {})",
+                 sensor.name,
+                 sensor.current,
+                 sensor.higher_warning_threshold,
+                 sensor.higher_alarm_threshold);
+    }
+}
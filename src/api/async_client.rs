@@ -0,0 +1,188 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2015 AT&T
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// Async variants of the port, route and initialization calls, running on tokio.
+// Enabled with the "async-client" feature.
+
+use async_hyper::{Body, Client, Method, Request, StatusCode};
+use async_hyper::body;
+use async_hyper::client::HttpConnector;
+use serde::de::DeserializeOwned;
+use serde_json;
+use std::sync::OnceLock;
+
+use super::envelope::{CreateResponse, ObjectEnvelope, ObjectList};
+use super::object::encode_key;
+use super::{ConfigMethod, ConfigObject, ConfigRequest, IPv4Route, IPv4RouteState, InitStep, Initialization,
+            JsonObject, PortState, PortStat, Route};
+use super::{decode_body, read_port_stats, read_routes, static_route};
+
+pub async fn get_ports_stats(connect_string: &str) -> Vec<PortStat> {
+    read_port_stats(&get_state_objects::<PortState>(connect_string, "Ports").await)
+}
+
+pub async fn get_routes(connect_string: &str) -> Vec<Route> {
+    read_routes(&get_state_objects::<IPv4RouteState>(connect_string, "IPv4Routes").await)
+}
+
+pub async fn add_route(connect_string: &str, route_from: &str, route_to: &str) -> Option<String> {
+    let data = serde_json::to_string(&log_error(static_route(route_from, Some(route_to)))?).unwrap();
+    let address = format!("http://{}/public/v1/config/IPv4Route", connect_string);
+
+    let body = log_error(send_request(Method::POST, &address, data).await)?;
+    decode_body::<CreateResponse>(&body).ok().map(|create_response| create_response.object_id)
+}
+
+// looks the route up by its key and deletes it by ObjectId, like the blocking delete_route
+pub async fn delete_route(connect_string: &str, route_from: &str) {
+    let key = match log_error(static_route(route_from, None).and_then(|route| encode_key(&route))) {
+        Some(key) => key,
+        None => return,
+    };
+
+    match get_object_id::<IPv4Route>(connect_string, &key).await {
+        Ok(Some(object_id)) => {
+            let address = format!("http://{}/public/v1/config/IPv4Route/{}", connect_string, object_id);
+            log_error(send_request(Method::DELETE, &address, "".to_string()).await);
+        }
+        Ok(None) => println!("error unknown {}", IPv4Route::object_name()),
+        Err(err) => println!("error {}", err),
+    }
+}
+
+pub async fn reset_config(connect_string: &str) -> Result<(), String> {
+    let address = format!("http://{}/public/v1/action/ResetConfig", connect_string);
+    send_request(Method::POST, &address, "".to_string())
+        .await
+        .map(|_| ())
+        .map_err(|err| format!("ResetConfig failed with {}", err))
+}
+
+pub async fn reset_and_initalize(connect_string: &str, config_file: &str) {
+    let mut initialization = Initialization::new(config_file);
+
+    loop {
+        match initialization.next_step() {
+            InitStep::ResetConfig => {
+                if let Err(err) = reset_config(connect_string).await {
+                    println!("error {}", err);
+                }
+            }
+            InitStep::GetPortNames => {
+                initialization.port_names = get_state_objects::<PortState>(connect_string, "Ports")
+                    .await
+                    .into_iter()
+                    .map(|port| port.IntfRef)
+                    .collect()
+            }
+            InitStep::Send(requests) => send_config_requests(connect_string, requests).await,
+            InitStep::Done => return,
+        }
+    }
+}
+
+async fn send_config_requests(connect_string: &str, requests: Vec<ConfigRequest>) {
+    for request in requests {
+        let address = format!("http://{}/public/v1/config/{}", connect_string, request.object_name);
+        let method = match request.method {
+            ConfigMethod::Post => Method::POST,
+            ConfigMethod::Patch => Method::PATCH,
+        };
+        log_error(send_request(method, &address, request.data).await);
+    }
+}
+
+// a missing object is not an error, the switch answers 404 for it
+async fn get_object_id<T: ConfigObject>(connect_string: &str, key: &JsonObject) -> Result<Option<String>, String> {
+    let address = format!("http://{}/public/v1/config/{}", connect_string, T::object_name());
+    let body = match send_request(Method::GET, &address, serde_json::to_string(key).unwrap()).await {
+        Ok(body) => body,
+        Err(ref err) if err.starts_with("404") => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    match decode_body::<ObjectEnvelope<JsonObject>>(&body)? {
+        ref envelope if envelope.object_id.is_empty() => Err(format!("missing ObjectId for {}", T::object_name())),
+        envelope => Ok(Some(envelope.object_id)),
+    }
+}
+
+// follows NextMarker across pages like the blocking get_state
+async fn get_state_objects<T: DeserializeOwned>(connect_string: &str, object_name: &str) -> Vec<T> {
+    let mut result = vec![];
+    let mut marker = 0;
+
+    loop {
+        let address = format!("http://{}/public/v1/state/{}?CurrentMarker={}", connect_string, object_name, marker);
+        let body = match log_error(send_request(Method::GET, &address, "".to_string()).await) {
+            Some(body) => body,
+            None => return result,
+        };
+
+        let object_list = match log_error(decode_body::<ObjectList<T>>(&body)) {
+            Some(object_list) => object_list,
+            None => return result,
+        };
+
+        result.extend(object_list.objects.into_iter().map(|envelope| envelope.object));
+        if !object_list.more_exist || object_list.next_marker == marker {
+            return result;
+        }
+        marker = object_list.next_marker;
+    }
+}
+
+// errors start with the http status code, like "404 Not Found: ..."
+async fn send_request(method: Method, address: &str, data: String) -> Result<String, String> {
+    let request = Request::builder()
+        .method(method)
+        .uri(address)
+        .header("Content-Type", "application/json")
+        .body(Body::from(data))
+        .map_err(|err| err.to_string())?;
+
+    let response = client().request(request).await.map_err(|err| err.to_string())?;
+    let status = response.status();
+    let bytes = body::to_bytes(response.into_body()).await.map_err(|err| err.to_string())?;
+    let body = String::from_utf8_lossy(&bytes).into_owned();
+
+    match status {
+        StatusCode::OK | StatusCode::CREATED => Ok(body),
+        status => Err(format!("{}: {}", status, body.trim())),
+    }
+}
+
+// one connection pool for all requests, hyper clients are cheap to clone
+fn client() -> Client<HttpConnector> {
+    static CLIENT: OnceLock<Client<HttpConnector>> = OnceLock::new();
+    CLIENT.get_or_init(Client::new).clone()
+}
+
+fn log_error<T>(result: Result<T, String>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            println!("error {}", err);
+            None
+        }
+    }
+}
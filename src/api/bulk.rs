@@ -0,0 +1,158 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2015 AT&T
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// Bulk route changes, spread over a fixed number of worker threads sharing one
+// keep-alive connection pool.
+
+use hyper::Client;
+use hyper::header::ContentType;
+use hyper::method::Method;
+use serde_json;
+use std::cmp;
+use std::io::Read;
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+
+use super::envelope::CreateResponse;
+use super::{decode_body, static_route};
+
+pub struct RouteResult {
+    pub from: String,
+    pub to: String,
+    // ObjectId of an added route, None for a deleted one
+    pub result: Result<Option<String>, String>,
+}
+
+struct RouteRequest {
+    from: String,
+    to: String,
+    // the encoded route, or why it could not be built
+    data: Result<String, String>,
+}
+
+// routes are (route_from, route_to) pairs like add_route takes them,
+// results come back in the order of the routes
+pub fn add_routes<I, S>(connect_string: &str, routes: I, concurrency: usize) -> Vec<RouteResult>
+    where I: IntoIterator<Item = (S, S)>,
+          S: Into<String>
+{
+    let requests = routes.into_iter()
+        .map(|(from, to)| {
+            let (from, to) = (from.into(), to.into());
+            let data = static_route(&from, Some(&to)).map(|route| serde_json::to_string(&route).unwrap());
+            RouteRequest {
+                from,
+                to,
+                data,
+            }
+        })
+        .collect();
+
+    send_route_requests(connect_string, Method::Post, requests, concurrency)
+}
+
+pub fn delete_routes<I, S>(connect_string: &str, routes: I, concurrency: usize) -> Vec<RouteResult>
+    where I: IntoIterator<Item = S>,
+          S: Into<String>
+{
+    let requests = routes.into_iter()
+        .map(|from| {
+            let from = from.into();
+            let data = static_route(&from, None).map(|route| serde_json::to_string(&route).unwrap());
+            RouteRequest {
+                from,
+                to: "".to_string(),
+                data,
+            }
+        })
+        .collect();
+
+    send_route_requests(connect_string, Method::Delete, requests, concurrency)
+}
+
+fn send_route_requests(connect_string: &str,
+                       method: Method,
+                       requests: Vec<RouteRequest>,
+                       concurrency: usize)
+                       -> Vec<RouteResult> {
+    let address = format!("http://{}/public/v1/config/IPv4Route", connect_string);
+    let client = Arc::new(Client::new());
+    let queue = Arc::new(Mutex::new(requests.into_iter().enumerate()));
+    let (sender, receiver) = mpsc::channel();
+
+    let mut workers = vec![];
+    for _ in 0..cmp::max(concurrency, 1) {
+        let address = address.clone();
+        let client = client.clone();
+        let queue = queue.clone();
+        let method = method.clone();
+        let sender = sender.clone();
+
+        workers.push(thread::spawn(move || loop {
+            let next = queue.lock().unwrap().next();
+            let (index, request) = match next {
+                Some(next) => next,
+                None => break,
+            };
+
+            let route_result = RouteResult {
+                result: match request.data {
+                    Ok(ref data) => send_route_request(&client, method.clone(), &address, data),
+                    Err(ref err) => Err(err.clone()),
+                },
+                from: request.from,
+                to: request.to,
+            };
+            sender.send((index, route_result)).unwrap();
+        }));
+    }
+    drop(sender);
+
+    let mut results: Vec<(usize, RouteResult)> = receiver.iter().collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    results.sort_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+fn send_route_request(client: &Client, method: Method, address: &str, data: &str) -> Result<Option<String>, String> {
+    let is_create = method == Method::Post;
+    let mut response = client.request(method, address)
+        .body(data)
+        .header(ContentType::json())
+        .send()
+        .map_err(|err| err.to_string())?;
+
+    let mut body = String::new();
+    response.read_to_string(&mut body).map_err(|err| err.to_string())?;
+
+    if response.status.to_u16() >= 400 {
+        return Err(format!("{}: {}", response.status, body.trim()));
+    }
+
+    match is_create {
+        true => decode_body::<CreateResponse>(&body).map(|create_response| Some(create_response.object_id)),
+        false => Ok(None),
+    }
+}
@@ -0,0 +1,67 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2015 AT&T
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// Typed envelopes FlexSwitch wraps its objects in.
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+// a single object, as returned by GET /public/v1/config/<Obj> or /public/v1/state/<Obj>
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ObjectEnvelope<T> {
+    #[serde(rename = "ObjectId", default)]
+    pub object_id: String,
+    #[serde(rename = "Object")]
+    pub object: T,
+}
+
+// one page of a bulk GET, the next page starts at next_marker while more_exist is set
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ObjectList<T> {
+    #[serde(rename = "MoreExist", default, deserialize_with = "null_as_default")]
+    pub more_exist: bool,
+    #[serde(rename = "ObjCount", default, deserialize_with = "null_as_default")]
+    pub obj_count: i64,
+    #[serde(rename = "CurrentMarker", default, deserialize_with = "null_as_default")]
+    pub current_marker: i64,
+    #[serde(rename = "NextMarker", default, deserialize_with = "null_as_default")]
+    pub next_marker: i64,
+    #[serde(rename = "Objects", default = "Vec::new", deserialize_with = "null_as_default",
+            bound(deserialize = "T: Deserialize<'de>"))]
+    pub objects: Vec<ObjectEnvelope<T>>,
+}
+
+// answer to a POST creating an object
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CreateResponse {
+    #[serde(rename = "ObjectId", default)]
+    pub object_id: String,
+    #[serde(rename = "Error", default)]
+    pub error: String,
+}
+
+// FlexSwitch sends null for empty lists and unset members, like "Objects": null
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where D: Deserializer<'de>,
+          T: Deserialize<'de> + Default
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
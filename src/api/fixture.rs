@@ -0,0 +1,225 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2015 AT&T
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// Recording and replay of switch traffic for fixture based tests. Enabled with
+// the "mock-server" feature.
+//
+// RecordingProxy sits in front of a real switch and saves every request/response
+// pair to a fixture file, ReplaySwitch serves them back:
+//
+//     let proxy = RecordingProxy::start("10.0.0.1:8080", "tests/fixtures/routes.json");
+//     api::get_routes(&proxy.connect_string());
+//
+//     let switch = ReplaySwitch::start("tests/fixtures/routes.json").unwrap();
+//     api::get_routes(&switch.connect_string());
+
+use hyper::Client;
+use hyper::header::ContentType;
+use hyper::method::Method;
+use hyper::server::{Listening, Request, Response};
+use hyper::status::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{self, Value};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+
+use super::mock::{read_request, start_server};
+
+// one request/response pair of a fixture file, bodies are kept as json where they
+// parse as json and as a string otherwise, so captured payloads stay readable
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    pub method: String,
+    pub path: String,
+    #[serde(default)]
+    pub request: Value,
+    pub status: u16,
+    #[serde(default)]
+    pub response: Value,
+}
+
+pub struct RecordingProxy {
+    listening: Listening,
+}
+
+pub struct ReplaySwitch {
+    listening: Listening,
+}
+
+impl RecordingProxy {
+    // forwards to the switch at connect_string, rewriting fixture_file after every exchange
+    pub fn start(connect_string: &str, fixture_file: &str) -> RecordingProxy {
+        let connect_string = connect_string.to_string();
+        let fixture_file = fixture_file.to_string();
+        let exchanges = Arc::new(Mutex::new(vec![]));
+
+        let listening = start_server(move |request: Request, response: Response| {
+            record_exchange(&connect_string, &fixture_file, &exchanges, request, response)
+        });
+
+        RecordingProxy { listening }
+    }
+
+    pub fn connect_string(&self) -> String {
+        self.listening.socket.to_string()
+    }
+}
+
+impl Drop for RecordingProxy {
+    fn drop(&mut self) {
+        self.listening.close().unwrap_or(());
+    }
+}
+
+impl ReplaySwitch {
+    pub fn start(fixture_file: &str) -> Result<ReplaySwitch, String> {
+        let exchanges = read_fixture(fixture_file)?;
+        let replayed = Arc::new(Mutex::new(vec![false; exchanges.len()]));
+
+        let listening = start_server(move |request: Request, response: Response| {
+            replay_exchange(&exchanges, &replayed, request, response)
+        });
+
+        Ok(ReplaySwitch { listening })
+    }
+
+    pub fn connect_string(&self) -> String {
+        self.listening.socket.to_string()
+    }
+}
+
+impl Drop for ReplaySwitch {
+    fn drop(&mut self) {
+        self.listening.close().unwrap_or(());
+    }
+}
+
+pub fn read_fixture(fixture_file: &str) -> Result<Vec<Exchange>, String> {
+    let mut content = String::new();
+    File::open(fixture_file)
+        .and_then(|mut file| file.read_to_string(&mut content))
+        .map_err(|err| format!("{}: {}", fixture_file, err))?;
+
+    serde_json::from_str(&content).map_err(|err| format!("invalid fixture {}: {}", fixture_file, err))
+}
+
+pub fn write_fixture(fixture_file: &str, exchanges: &[Exchange]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(exchanges).unwrap();
+    File::create(fixture_file)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|err| format!("{}: {}", fixture_file, err))
+}
+
+fn record_exchange(connect_string: &str,
+                   fixture_file: &str,
+                   exchanges: &Mutex<Vec<Exchange>>,
+                   mut request: Request,
+                   mut response: Response) {
+    let (path, body) = read_request(&mut request);
+
+    let (status, reply) = match forward_request(connect_string, &request.method, &path, &body) {
+        Ok((status, reply)) => {
+            let mut exchanges = exchanges.lock().unwrap();
+            exchanges.push(Exchange {
+                method: request.method.to_string(),
+                path,
+                request: body_value(&body),
+                status: status.to_u16(),
+                response: body_value(&reply),
+            });
+            if let Err(err) = write_fixture(fixture_file, &exchanges) {
+                println!("error {}", err);
+            }
+            (status, reply)
+        }
+        Err(err) => (StatusCode::BadGateway, err),
+    };
+
+    *response.status_mut() = status;
+    response.send(reply.as_bytes()).unwrap_or(());
+}
+
+fn forward_request(connect_string: &str, method: &Method, path: &str, body: &str) -> Result<(StatusCode, String), String> {
+    let client = Client::new();
+    let address = format!("http://{}{}", connect_string, path);
+
+    // hyper drops the body of a GET, like in send_config_request
+    let method = match *method {
+        Method::Get if !body.is_empty() => Method::Extension("GET".to_string()),
+        ref method => method.clone(),
+    };
+
+    let mut upstream = client.request(method, &address)
+        .body(body)
+        .header(ContentType::json())
+        .send()
+        .map_err(|err| format!("{}: {}", address, err))?;
+
+    let mut reply = String::new();
+    upstream.read_to_string(&mut reply).map_err(|err| format!("{}: {}", address, err))?;
+
+    Ok((upstream.status, reply))
+}
+
+// the first exchange not replayed yet for the same request, repeated requests
+// get the last matching exchange again
+fn replay_exchange(exchanges: &[Exchange], replayed: &Mutex<Vec<bool>>, mut request: Request, mut response: Response) {
+    let (path, body) = read_request(&mut request);
+    let method = request.method.to_string();
+    let body = body_value(&body);
+
+    let matching: Vec<usize> = exchanges.iter()
+        .enumerate()
+        .filter(|(_, exchange)| exchange.method == method && exchange.path == path && exchange.request == body)
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut replayed = replayed.lock().unwrap();
+    let (status, reply) = match matching.iter().find(|&&index| !replayed[index]).or_else(|| matching.last()) {
+        Some(&index) => {
+            replayed[index] = true;
+            let exchange = &exchanges[index];
+            (StatusCode::from_u16(exchange.status), body_string(&exchange.response))
+        }
+        None => (StatusCode::NotFound, format!("no recorded response for {} {}", method, path)),
+    };
+    drop(replayed);
+
+    *response.status_mut() = status;
+    response.send(reply.as_bytes()).unwrap_or(());
+}
+
+fn body_value(body: &str) -> Value {
+    match body.trim().is_empty() {
+        true => Value::Null,
+        false => serde_json::from_str(body).unwrap_or_else(|_| Value::from(body)),
+    }
+}
+
+fn body_string(body: &Value) -> String {
+    match *body {
+        Value::Null => "".to_string(),
+        Value::String(ref body) => body.clone(),
+        ref body => body.to_string(),
+    }
+}
@@ -0,0 +1,387 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2015 AT&T
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// In-process FlexSwitch emulation for tests, keeping config and state objects
+// in memory. Enabled with the "mock-server" feature.
+//
+//     let switch = MockSwitch::start();
+//     switch.add_port("fpPort1", 1);
+//     api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1");
+//     assert_eq!(api::get_routes(&switch.connect_string()).len(), 1);
+
+use hyper::method::Method;
+use hyper::server::{Handler, Listening, Request, Response, Server};
+use hyper::status::StatusCode;
+use hyper::uri::RequestUri;
+use serde::Serialize;
+use serde_json::{self, Value};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+
+use super::object::key_fields_by_name;
+use super::{ConfigObject, JsonObject, Port, PortState};
+
+pub struct MockSwitch {
+    store: Arc<Mutex<Store>>,
+    listening: Listening,
+}
+
+#[derive(Default)]
+struct Store {
+    // (ObjectId, object) per object name, in creation order
+    config: BTreeMap<String, Vec<(String, JsonObject)>>,
+    // what ResetConfig goes back to
+    default_config: BTreeMap<String, Vec<(String, JsonObject)>>,
+    state: BTreeMap<String, Vec<Value>>,
+    singletons: BTreeMap<String, Value>,
+    // key fields of objects without bindings in api, see set_key_fields
+    key_fields: BTreeMap<String, Vec<String>>,
+    actions: Vec<String>,
+    page_size: Option<usize>,
+    last_object_id: u64,
+}
+
+impl MockSwitch {
+    // listens on a free port on 127.0.0.1 until dropped
+    pub fn start() -> MockSwitch {
+        let store = Arc::new(Mutex::new(Store::default()));
+
+        let handler_store = store.clone();
+        let listening = start_server(move |request: Request, response: Response| {
+            handle_request(&handler_store, request, response)
+        });
+
+        MockSwitch { store, listening }
+    }
+
+    pub fn connect_string(&self) -> String {
+        self.listening.socket.to_string()
+    }
+
+    // a front panel port like the switch reports it after boot, part of the default config
+    pub fn add_port(&self, intf_ref: &str, if_index: i32) {
+        let port = Port {
+            IntfRef: intf_ref.to_string(),
+            ..Default::default()
+        };
+        self.add_config_object(&port);
+
+        let port_state = PortState {
+            IntfRef: intf_ref.to_string(),
+            IfIndex: if_index,
+            OperState: "UP".to_string(),
+        };
+        self.add_state_object("Ports", &port_state);
+    }
+
+    // objects added here are the default config and come back after ResetConfig
+    pub fn add_config_object<T: ConfigObject>(&self, object: &T) -> String {
+        let object = match serde_json::to_value(object) {
+            Ok(Value::Object(object)) => object,
+            _ => panic!("{} does not encode to a json object", T::object_name()),
+        };
+
+        let mut store = self.store.lock().unwrap();
+        let store = &mut *store;
+        let object_id = store.next_object_id();
+        for config in &mut [&mut store.config, &mut store.default_config] {
+            config.entry(T::object_name().to_string()).or_default().push((object_id.clone(), object.clone()));
+        }
+
+        object_id
+    }
+
+    // object_name is the bulk name used in the url, like "Ports"
+    pub fn add_state_object<T: Serialize>(&self, object_name: &str, object: &T) {
+        let object = serde_json::to_value(object).unwrap();
+        self.store.lock().unwrap().state.entry(object_name.to_string()).or_default().push(object);
+    }
+
+    // state objects returned on their own, like "SystemStatus"
+    pub fn set_state_object<T: Serialize>(&self, object_name: &str, object: &T) {
+        let object = serde_json::to_value(object).unwrap();
+        self.store.lock().unwrap().singletons.insert(object_name.to_string(), object);
+    }
+
+    // objects without bindings in api can only be found by ObjectId, unless their key fields are set here
+    pub fn set_key_fields(&self, object_name: &str, key_fields: &[&str]) {
+        let key_fields = key_fields.iter().map(|field| field.to_string()).collect();
+        self.store.lock().unwrap().key_fields.insert(object_name.to_string(), key_fields);
+    }
+
+    // splits bulk responses into pages of page_size objects linked through NextMarker
+    pub fn set_page_size(&self, page_size: usize) {
+        self.store.lock().unwrap().page_size = Some(page_size);
+    }
+
+    pub fn config_objects(&self, object_name: &str) -> Vec<JsonObject> {
+        match self.store.lock().unwrap().config.get(object_name) {
+            Some(objects) => objects.iter().map(|(_, object)| object.clone()).collect(),
+            None => vec![],
+        }
+    }
+
+    // names of the actions posted so far, like "ResetConfig"
+    pub fn actions(&self) -> Vec<String> {
+        self.store.lock().unwrap().actions.clone()
+    }
+}
+
+impl Drop for MockSwitch {
+    fn drop(&mut self) {
+        // without close the listener's drop waits for the server threads forever
+        self.listening.close().unwrap_or(());
+    }
+}
+
+impl Store {
+    fn next_object_id(&mut self) -> String {
+        self.last_object_id += 1;
+        format!("00000000-0000-0000-0000-{:012x}", self.last_object_id)
+    }
+
+    fn is_config_object(&self, object_name: &str) -> bool {
+        key_fields_by_name(object_name).is_some() || self.key_fields.contains_key(object_name) ||
+        self.config.contains_key(object_name)
+    }
+
+    fn key_fields(&self, object_name: &str) -> Vec<&str> {
+        match key_fields_by_name(object_name) {
+            Some(key_fields) => key_fields.to_vec(),
+            None => {
+                self.key_fields
+                    .get(object_name)
+                    .map(|key_fields| key_fields.iter().map(|field| field.as_str()).collect())
+                    .unwrap_or_default()
+            }
+        }
+    }
+
+    // the object given by ObjectId, or else by the key fields of the request body,
+    // an object without known key fields never matches by key
+    fn find(&self, object_name: &str, object_id: Option<&str>, key: &JsonObject) -> Option<usize> {
+        let objects = self.config.get(object_name)?;
+        let key_fields = self.key_fields(object_name);
+
+        objects.iter().position(|(id, object)| match object_id {
+            Some(object_id) => id == object_id,
+            None => !key_fields.is_empty() && key_fields.iter().all(|field| object.get(*field) == key.get(*field)),
+        })
+    }
+
+    // state view of config objects the switch reports back, like static routes
+    fn state_objects(&self, object_name: &str) -> Vec<Value> {
+        let mut result = self.state.get(object_name).cloned().unwrap_or_default();
+
+        if object_name == "IPv4Routes" {
+            for (_, route) in self.config.get("IPv4Route").into_iter().flatten() {
+                result.push(route_state(route));
+            }
+        }
+
+        result
+    }
+
+    fn page(&self, objects: Vec<(String, Value)>, marker: usize) -> Value {
+        let start = marker.min(objects.len());
+        let end = match self.page_size {
+            Some(page_size) => (start + page_size).min(objects.len()),
+            None => objects.len(),
+        };
+
+        let page: Vec<Value> = objects[start..end]
+            .iter()
+            .map(|(object_id, object)| json_envelope(object_id, object.clone()))
+            .collect();
+
+        let mut object_list = JsonObject::new();
+        object_list.insert("MoreExist".to_string(), Value::from(end < objects.len()));
+        object_list.insert("ObjCount".to_string(), Value::from(page.len()));
+        object_list.insert("CurrentMarker".to_string(), Value::from(start));
+        object_list.insert("NextMarker".to_string(), Value::from(end));
+        object_list.insert("Objects".to_string(), Value::from(page));
+        Value::Object(object_list)
+    }
+}
+
+// listens on a free port on 127.0.0.1, keep-alive is off so every request gets its own connection
+pub(super) fn start_server<H: Handler + 'static>(handler: H) -> Listening {
+    let mut server = Server::http("127.0.0.1:0").unwrap();
+    server.keep_alive(None);
+    server.handle_threads(handler, 4).unwrap()
+}
+
+// path with query and body of a request
+pub(super) fn read_request(request: &mut Request) -> (String, String) {
+    let uri = match request.uri {
+        RequestUri::AbsolutePath(ref path) => path.clone(),
+        _ => "".to_string(),
+    };
+
+    let mut body = String::new();
+    request.read_to_string(&mut body).unwrap_or(0);
+    (uri, body)
+}
+
+fn handle_request(store: &Mutex<Store>, mut request: Request, mut response: Response) {
+    let (uri, body) = read_request(&mut request);
+    let data = match serde_json::from_str(&body) {
+        Ok(Value::Object(data)) => data,
+        _ => JsonObject::new(),
+    };
+
+    let (path, query) = match uri.find('?') {
+        Some(index) => (&uri[..index], &uri[index + 1..]),
+        None => (&uri[..], ""),
+    };
+    let marker = query.split('&')
+        .filter_map(|parameter| parameter.strip_prefix("CurrentMarker="))
+        .filter_map(|marker| marker.parse().ok())
+        .next()
+        .unwrap_or(0);
+
+    let mut store = store.lock().unwrap();
+    let (status, reply) = if let Some(path) = path.strip_prefix("/public/v1/config/") {
+        handle_config(&mut store, &request.method, path, marker, data)
+    } else if let Some(object_name) = path.strip_prefix("/public/v1/state/") {
+        handle_state(&store, object_name, marker)
+    } else if let Some(action_name) = path.strip_prefix("/public/v1/action/") {
+        handle_action(&mut store, &request.method, action_name)
+    } else {
+        error_reply(StatusCode::NotFound, &format!("unknown url {}", uri))
+    };
+    drop(store);
+
+    *response.status_mut() = status;
+    response.send(reply.to_string().as_bytes()).unwrap_or(());
+}
+
+fn handle_config(store: &mut Store, method: &Method, path: &str, marker: usize, data: JsonObject) -> (StatusCode, Value) {
+    let (object_name, object_id) = match path.find('/') {
+        Some(index) => (&path[..index], Some(&path[index + 1..])),
+        None => (path, None),
+    };
+
+    // the bulk url is the plural of the object name, like Vlans
+    if *method == Method::Get && object_id.is_none() && !store.is_config_object(object_name) &&
+       object_name.ends_with('s') {
+        let object_name = &object_name[..object_name.len() - 1];
+        let objects = store.config
+            .get(object_name)
+            .into_iter()
+            .flatten()
+            .map(|(object_id, object)| (object_id.clone(), Value::Object(object.clone())))
+            .collect();
+        return (StatusCode::Ok, store.page(objects, marker));
+    }
+
+    let index = store.find(object_name, object_id, &data);
+    match (method, index) {
+        (&Method::Post, None) => {
+            let object_id = store.next_object_id();
+            store.config.entry(object_name.to_string()).or_default().push((object_id.clone(), data));
+            (StatusCode::Created, create_reply(&object_id))
+        }
+        (&Method::Post, Some(_)) => error_reply(StatusCode::InternalServerError, "object exists"),
+        (&Method::Get, Some(index)) => {
+            let (object_id, object) = &store.config[object_name][index];
+            (StatusCode::Ok, json_envelope(object_id, Value::Object(object.clone())))
+        }
+        (&Method::Patch, Some(index)) => {
+            let (object_id, object) = &mut store.config.get_mut(object_name).unwrap()[index];
+            object.extend(data);
+            (StatusCode::Ok, create_reply(object_id))
+        }
+        (&Method::Delete, Some(index)) => {
+            let (object_id, _) = store.config.get_mut(object_name).unwrap().remove(index);
+            (StatusCode::Ok, create_reply(&object_id))
+        }
+        (_, None) => error_reply(StatusCode::NotFound, &format!("unknown {}", object_name)),
+        (_, Some(_)) => error_reply(StatusCode::MethodNotAllowed, &format!("unsupported method {}", method)),
+    }
+}
+
+fn handle_state(store: &Store, object_name: &str, marker: usize) -> (StatusCode, Value) {
+    if let Some(object) = store.singletons.get(object_name) {
+        return (StatusCode::Ok, json_envelope("", object.clone()));
+    }
+
+    if !object_name.ends_with('s') {
+        return error_reply(StatusCode::NotFound, &format!("unknown {}", object_name));
+    }
+
+    let objects = store.state_objects(object_name).into_iter().map(|object| ("".to_string(), object)).collect();
+    (StatusCode::Ok, store.page(objects, marker))
+}
+
+fn handle_action(store: &mut Store, method: &Method, action_name: &str) -> (StatusCode, Value) {
+    if *method != Method::Post {
+        return error_reply(StatusCode::MethodNotAllowed, &format!("unsupported method {}", method));
+    }
+
+    if action_name == "ResetConfig" {
+        store.config = store.default_config.clone();
+    }
+    store.actions.push(action_name.to_string());
+
+    (StatusCode::Ok, create_reply(""))
+}
+
+// IPv4Route config as the switch reports it in IPv4RouteState
+fn route_state(route: &JsonObject) -> Value {
+    let destination = route.get("DestinationNw").and_then(|value| value.as_str()).unwrap_or("");
+    let mask = route.get("NetworkMask").and_then(|value| value.as_str()).unwrap_or("");
+    let prefix_len: u32 = mask.split('.').filter_map(|octet| octet.parse::<u8>().ok()).map(|octet| octet.count_ones()).sum();
+
+    let next_hops: Vec<Value> = match route.get("NextHop") {
+        Some(Value::Array(next_hops)) => next_hops.clone(),
+        _ => vec![],
+    };
+
+    let mut state = JsonObject::new();
+    state.insert("DestinationNw".to_string(), Value::from(format!("{}/{}", destination, prefix_len)));
+    state.insert("Protocol".to_string(), route.get("Protocol").cloned().unwrap_or_else(|| Value::from("STATIC")));
+    state.insert("NextHopList".to_string(), Value::from(next_hops));
+    Value::Object(state)
+}
+
+fn json_envelope(object_id: &str, object: Value) -> Value {
+    let mut envelope = JsonObject::new();
+    envelope.insert("ObjectId".to_string(), Value::from(object_id));
+    envelope.insert("Object".to_string(), object);
+    Value::Object(envelope)
+}
+
+fn create_reply(object_id: &str) -> Value {
+    let mut reply = JsonObject::new();
+    reply.insert("ObjectId".to_string(), Value::from(object_id));
+    reply.insert("Error".to_string(), Value::from(""));
+    Value::Object(reply)
+}
+
+fn error_reply(status: StatusCode, error: &str) -> (StatusCode, Value) {
+    let mut reply = JsonObject::new();
+    reply.insert("ObjectId".to_string(), Value::from(""));
+    reply.insert("Error".to_string(), Value::from(error));
+    (status, Value::Object(reply))
+}
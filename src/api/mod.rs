@@ -23,11 +23,47 @@
 use hyper::Client;
 use hyper::header::ContentType;
 use hyper::status::StatusCode;
-use rustc_serialize::json::{self, Json};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json;
+use std::cmp;
 use std::fs::File;
 use std::io::Read;
+use std::thread;
+use std::time::{Duration, Instant};
 use yaml_rust::{Yaml, YamlLoader};
 
+pub use self::bulk::{RouteResult, add_routes, delete_routes};
+pub use self::api_models::{IPv4Route, NextHopInfo, Port};
+pub use self::envelope::{CreateResponse, ObjectEnvelope, ObjectList};
+pub use self::object::{ConfigObject, JsonObject};
+pub use self::object::{create, get, get_by_key, get_by_id, get_object_id, get_all, update, update_by_id, delete, delete_by_id};
+pub use self::object::{create_json, get_json_by_key, get_json_by_id, get_json_object_id, get_all_json, update_json,
+                       update_json_by_id, delete_json, delete_json_by_id};
+
+mod bulk;
+mod envelope;
+mod object;
+
+#[cfg(feature = "async-client")]
+pub mod async_client;
+
+#[cfg(feature = "mock-server")]
+pub mod fixture;
+
+#[cfg(feature = "mock-server")]
+pub mod mock;
+
+#[cfg(feature = "models")]
+pub mod models {
+    include!(concat!(env!("OUT_DIR"), "/models.rs"));
+}
+
+// Port, IPv4Route and NextHopInfo, generated from the models directory of this crate
+mod api_models {
+    include!(concat!(env!("OUT_DIR"), "/api_models.rs"));
+}
+
 macro_rules! log_request_error {
     ($expr:expr) => (match $expr {
         Result::Ok(response) => {
@@ -43,224 +79,2407 @@ macro_rules! log_request_error {
     })
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PortStat {
     pub id: i32,
     pub connected: bool,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Route {
     pub from: String,
     pub to: String,
+    pub protocol: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RouteDistanceStat {
+    pub protocol: String,
+    pub distance: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RouteCount {
+    pub protocol: String,
+    pub route_count: i32,
+    pub ecmp_count: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MacTableEntry {
+    pub mac_addr: String,
+    pub vlan_id: i32,
+    pub port: i32,
+    pub is_static: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BfdSessionStat {
+    pub ip_addr: String,
+    pub if_name: String,
+    pub param_name: String,
+    pub session_state: String,
+    pub remote_session_state: String,
+    pub detection_multiplier: i32,
+    pub desired_min_tx_interval: i32,
+    pub required_min_rx_interval: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct VrrpStat {
+    pub intf_ref: String,
+    pub vrid: i32,
+    pub virtual_ip: String,
+    pub priority: i32,
+    pub state: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DhcpRelayStat {
+    pub intf_ref: String,
+    pub total_drops: i32,
+    pub client_rx: i32,
+    pub client_tx: i32,
+    pub server_rx: i32,
+    pub server_tx: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StpBridgeStat {
+    pub vlan: i32,
+    pub bridge_id: String,
+    pub designated_root: String,
+    pub root_port: i32,
+    pub root_path_cost: i32,
+    pub topology_changes: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StpPortStat {
+    pub intf_ref: String,
+    pub vlan: i32,
+    pub role: String,
+    pub state: String,
+    pub designated_root: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AclStat {
+    pub acl_name: String,
+    pub rule_name: String,
+    pub intf_list: Vec<String>,
+    pub hit_count: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NeighborEntry {
+    pub ip_addr: String,
+    pub mac_addr: String,
+    pub vlan_id: i32,
+    pub intf_ref: String,
+    pub port: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FanStat {
+    pub fan_id: i32,
+    pub oper_speed: i32,
+    pub oper_direction: String,
+    pub status: String,
+    pub model: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PsuStat {
+    pub psu_id: i32,
+    pub admin_state: String,
+    pub model: String,
+    pub serial_num: String,
+    pub vin: i32,
+    pub vout: i32,
+    pub iin: i32,
+    pub iout: i32,
+    pub pin: i32,
+    pub pout: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SensorReading {
+    pub name: String,
+    pub current: f64,
+    pub higher_alarm_threshold: f64,
+    pub higher_warning_threshold: f64,
+    pub lower_warning_threshold: f64,
+    pub lower_alarm_threshold: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlatformStat {
+    pub product_name: String,
+    pub platform_name: String,
+    pub manufacturer: String,
+    pub vendor: String,
+    pub serial_num: String,
+    pub release: String,
+    pub version: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Transceiver {
+    pub id: i32,
+    pub kind: String,
+    pub port_ids: Vec<i32>,
+    pub present: bool,
+    pub vendor: String,
+    pub part_number: String,
+    pub serial_number: String,
+    pub wavelength: f64,
+    pub temperature: f64,
+    pub voltage: f64,
+    pub rx_power: Vec<f64>,
+    pub tx_power: Vec<f64>,
+    pub tx_bias: Vec<f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DaemonStat {
+    pub name: String,
+    pub enabled: bool,
+    pub state: String,
+    pub reason: String,
+    pub start_time: String,
+    pub restart_count: i32,
+    pub restart_reason: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SystemStat {
+    pub name: String,
+    pub ready: bool,
+    pub reason: String,
+    pub up_time: String,
+    pub daemons: Vec<DaemonStat>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RepoStat {
+    pub name: String,
+    pub sha1: String,
+    pub branch: String,
+    pub time: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SwVersionStat {
+    pub flexswitch_version: String,
+    pub repos: Vec<RepoStat>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LLDPNeighbor {
+    pub port_id: i32,
+    pub intf_ref: String,
+    pub peer_chassis_id: String,
+    pub peer_port_id: String,
+    pub peer_system_name: String,
+    pub peer_mgmt_addr: String,
+    pub peer_capabilities: String,
 }
 
 pub fn get_ports_stats(connect_string: &str) -> Vec<PortStat> {
+    read_port_stats(&get_typed_state_objects(connect_string, "Ports"))
+}
+
+fn read_port_stats(ports: &[PortState]) -> Vec<PortStat> {
     let mut result = vec![];
 
-    let client = Client::new();
-    let address = format!("http://{}/public/v1/state/Ports", connect_string);
+    for port in ports {
+        result.push(PortStat {
+            id: port.IfIndex,
+            connected: port.OperState == "UP",
+        })
+    }
 
-    let mut response = match log_request_error!(client.get(&address).send()) {
-        Some(response) => response,
-        None => return result,
-    };
+    result
+}
 
-    match response.status {
-        StatusCode::Ok => {
-            let mut body = String::new();
-            response.read_to_string(&mut body).unwrap();
-
-            let jsondata = Json::from_str(&body.clone()).unwrap();
-            let snap_objects_raw = jsondata.as_object().unwrap();
-            let snap_objects = snap_objects_raw.get("Objects").unwrap().as_array().unwrap();
-            for snap_object in snap_objects.iter() {
-                let mut connected = false;
-                let id = snap_object.search("IfIndex").unwrap().as_u64().unwrap();
-
-                let port_status = snap_object.search("OperState").unwrap();
-                if port_status.as_string().unwrap() == "UP" {
-                    connected = true
-                }
+pub fn get_routes(connect_string: &str) -> Vec<Route> {
+    read_routes(&get_typed_state_objects(connect_string, "IPv4Routes"))
+}
 
-                result.push(PortStat {
-                    id: id as i32,
-                    connected: connected,
-                })
-            }
+fn read_routes(routes: &[IPv4RouteState]) -> Vec<Route> {
+    let mut result = vec![];
+
+    for route in routes {
+        let mut to = "".to_string();
+        if let Some(nexthop) = route.NextHopList.first() {
+            to = nexthop.NextHopIp.clone();
         }
-        _ => println!("error code {}", response.status),
+
+        result.push(Route {
+            from: route.DestinationNw.clone(),
+            to,
+            protocol: route.Protocol.clone(),
+        })
     }
 
     result
 }
 
-pub fn get_routes(connect_string: &str) -> Vec<Route> {
+pub fn get_mac_table(connect_string: &str) -> Vec<MacTableEntry> {
     let mut result = vec![];
 
-    let client = Client::new();
-    let address = format!("http://{}/public/v1/state/IPv4Routes", connect_string);
+    for entry in get_typed_state_objects::<MacTableEntryState>(connect_string, "MacTableEntrys") {
+        result.push(MacTableEntry {
+            mac_addr: entry.MacAddr,
+            vlan_id: entry.VlanId,
+            port: entry.Port,
+            // learned entries carry no type, only static entries are marked
+            is_static: entry.Type == "STATIC",
+        })
+    }
 
-    let mut response = match log_request_error!(client.get(&address).send()) {
-        Some(response) => response,
-        None => return result,
-    };
+    result
+}
 
-    match response.status {
-        StatusCode::Ok => {
-            let mut body = String::new();
-            response.read_to_string(&mut body).unwrap();
-
-            let jsondata = Json::from_str(&body.clone()).unwrap();
-            let snap_objects_raw = jsondata.as_object().unwrap();
-            let snap_objects = snap_objects_raw.get("Objects").unwrap().as_array().unwrap();
-            for snap_object in snap_objects.iter() {
-                let from = snap_object.search("DestinationNw").unwrap().to_string();
-                let mut to = "".to_string();
-
-                let nexthoplist = snap_object.search("NextHopList").unwrap().as_array().unwrap();
-                if nexthoplist.len() > 0 {
-                    to = nexthoplist[0].search("NextHopIp").unwrap().to_string();
-                }
+pub fn get_mac_table_by_vlan(connect_string: &str, vlan_id: i32) -> Vec<MacTableEntry> {
+    get_mac_table(connect_string).into_iter().filter(|entry| entry.vlan_id == vlan_id).collect()
+}
 
-                result.push(Route {
-                    from: from,
-                    to: to,
-                })
-            }
+pub fn get_mac_table_by_port(connect_string: &str, port: i32) -> Vec<MacTableEntry> {
+    get_mac_table(connect_string).into_iter().filter(|entry| entry.port == port).collect()
+}
+
+pub fn get_ndp_table(connect_string: &str) -> Vec<NeighborEntry> {
+    let mut result = vec![];
+
+    for entry in get_typed_state_objects::<NDPEntryState>(connect_string, "NDPEntrys") {
+        result.push(NeighborEntry {
+            ip_addr: entry.IpAddr,
+            mac_addr: entry.MacAddr,
+            vlan_id: entry.Vlan,
+            intf_ref: entry.Intf,
+            port: entry.IfIndex,
+        })
+    }
+
+    result
+}
+
+pub fn get_route_distances(connect_string: &str) -> Vec<RouteDistanceStat> {
+    let mut result = vec![];
+
+    for route_distance in get_typed_state_objects::<RouteDistanceState>(connect_string, "RouteDistances") {
+        result.push(RouteDistanceStat {
+            protocol: route_distance.Protocol,
+            distance: route_distance.Distance,
+        })
+    }
+
+    result
+}
+
+pub fn get_route_counts(connect_string: &str) -> Vec<RouteCount> {
+    let mut result = vec![];
+
+    for route_count in get_typed_state_objects::<PerProtocolRouteCountState>(connect_string, "PerProtocolRouteCounts") {
+        result.push(RouteCount {
+            protocol: route_count.Protocol,
+            route_count: route_count.RouteCount,
+            ecmp_count: route_count.EcmpCount,
+        })
+    }
+
+    result
+}
+
+pub fn get_fans(connect_string: &str) -> Vec<FanStat> {
+    let mut result = vec![];
+
+    for fan in get_typed_state_objects::<FanState>(connect_string, "Fans") {
+        result.push(FanStat {
+            fan_id: fan.FanId,
+            oper_speed: fan.OperSpeed,
+            oper_direction: fan.OperDirection,
+            status: fan.Status,
+            model: fan.Model,
+        })
+    }
+
+    result
+}
+
+pub fn get_psus(connect_string: &str) -> Vec<PsuStat> {
+    let mut result = vec![];
+
+    for psu in get_typed_state_objects::<PsuState>(connect_string, "Psus") {
+        result.push(PsuStat {
+            psu_id: psu.PsuId,
+            admin_state: psu.AdminState,
+            model: psu.ModelNum,
+            serial_num: psu.SerialNum,
+            vin: psu.Vin,
+            vout: psu.Vout,
+            iin: psu.Iin,
+            iout: psu.Iout,
+            pin: psu.Pin,
+            pout: psu.Pout,
+        })
+    }
+
+    result
+}
+
+pub fn get_temperature_sensors(connect_string: &str) -> Vec<SensorReading> {
+    get_sensor_readings(connect_string, "TemperatureSensors", |sensor| sensor.CurrentTemperature)
+}
+
+pub fn get_voltage_sensors(connect_string: &str) -> Vec<SensorReading> {
+    get_sensor_readings(connect_string, "VoltageSensors", |sensor| sensor.CurrentVoltage)
+}
+
+pub fn get_platform(connect_string: &str) -> Option<PlatformStat> {
+    let platforms = get_typed_state_objects::<PlatformState>(connect_string, "Platforms");
+
+    platforms.into_iter().next().map(|platform| {
+        PlatformStat {
+            product_name: platform.ProductName,
+            platform_name: platform.PlatformName,
+            manufacturer: platform.Manufacturer,
+            vendor: platform.Vendor,
+            serial_num: platform.SerialNum,
+            release: platform.Release,
+            version: platform.Version,
         }
-        _ => println!("error code {}", response.status),
+    })
+}
+
+// port_ids holds the IfIndex of every port served by the transceiver, more than one for breakout ports
+pub fn get_transceivers(connect_string: &str) -> Vec<Transceiver> {
+    let mut result = vec![];
+    let port_indexes = get_port_indexes(connect_string);
+
+    for qsfp in get_typed_state_objects::<QsfpState>(connect_string, "Qsfps") {
+        result.push(Transceiver {
+            id: qsfp.QsfpId,
+            kind: "QSFP".to_string(),
+            port_ids: transceiver_port_ids(&port_indexes, qsfp.QsfpId),
+            present: qsfp.Present,
+            vendor: qsfp.VendorName,
+            part_number: qsfp.VendorPartNumber,
+            serial_number: qsfp.VendorSerialNumber,
+            wavelength: qsfp.Wavelength,
+            temperature: qsfp.Temperature,
+            voltage: qsfp.SupplyVoltage,
+            rx_power: read_lanes(&[qsfp.RX1Power, qsfp.RX2Power, qsfp.RX3Power, qsfp.RX4Power]),
+            tx_power: read_lanes(&[qsfp.TX1Power, qsfp.TX2Power, qsfp.TX3Power, qsfp.TX4Power]),
+            tx_bias: read_lanes(&[qsfp.TX1Bias, qsfp.TX2Bias, qsfp.TX3Bias, qsfp.TX4Bias]),
+        })
+    }
+
+    for sfp in get_typed_state_objects::<SfpState>(connect_string, "Sfps") {
+        result.push(Transceiver {
+            id: sfp.SfpId,
+            kind: "SFP".to_string(),
+            port_ids: transceiver_port_ids(&port_indexes, sfp.SfpId),
+            present: sfp.SfpPresent,
+            vendor: sfp.VendorName,
+            part_number: sfp.VendorPN,
+            serial_number: sfp.VendorSN,
+            wavelength: sfp.Wavelength,
+            temperature: sfp.Temperature,
+            voltage: sfp.SupplyVoltage,
+            rx_power: vec![sfp.RXPower],
+            tx_power: vec![sfp.TXPower],
+            tx_bias: vec![sfp.TXBias],
+        })
     }
 
     result
 }
 
+pub fn get_system_status(connect_string: &str) -> Option<SystemStat> {
+    get_state_object::<SystemStatusState>(connect_string, "SystemStatus").map(|system| {
+        SystemStat {
+            name: system.Name,
+            ready: system.Ready,
+            reason: system.Reason,
+            up_time: system.UpTime,
+            daemons: system.FlexDaemons.into_iter().map(read_daemon_stat).collect(),
+        }
+    })
+}
+
+pub fn get_sw_version(connect_string: &str) -> Option<SwVersionStat> {
+    get_state_object::<SystemSwVersionState>(connect_string, "SystemSwVersion").map(|sw_version| {
+        let mut repos = vec![];
+        for repo in sw_version.Repos {
+            repos.push(RepoStat {
+                name: repo.Name,
+                sha1: repo.Sha1,
+                branch: repo.Branch,
+                time: repo.Time,
+            });
+        }
+
+        SwVersionStat {
+            flexswitch_version: sw_version.FlexswitchVersion,
+            repos,
+        }
+    })
+}
+
+pub fn get_daemons(connect_string: &str) -> Vec<DaemonStat> {
+    get_typed_state_objects::<DaemonState>(connect_string, "DaemonStates").into_iter().map(read_daemon_stat).collect()
+}
+
 pub fn reset_routes(_connect_string: &str) {
     println!("reset routes not implemented for snaproute")
 }
 
 
-#[derive(Clone, RustcEncodable)]
+#[derive(Clone, Serialize, Deserialize)]
 #[allow(non_snake_case)]
-pub struct Port {
+pub struct PortState {
     pub IntfRef: String,
-    pub BreakOutMode: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub IfIndex: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub OperState: String,
 }
 
-#[derive(Clone, RustcEncodable)]
+#[derive(Clone, Serialize, Deserialize)]
 #[allow(non_snake_case)]
-pub struct SubPort {
-    pub IntfRef: String,
-    pub Speed: i32,
+pub struct NextHopState {
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub NextHopIp: String,
+}
+
+// switches leave out or null members of routes that are still being resolved
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct IPv4RouteState {
+    pub DestinationNw: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Protocol: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub NextHopList: Vec<NextHopState>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct MacTableEntryState {
+    pub MacAddr: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub VlanId: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Port: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Type: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct NDPEntryState {
+    pub IpAddr: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub MacAddr: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Vlan: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Intf: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub IfIndex: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct RouteDistanceState {
+    pub Protocol: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Distance: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct PerProtocolRouteCountState {
+    pub Protocol: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RouteCount: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub EcmpCount: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct FanState {
+    pub FanId: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub OperSpeed: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub OperDirection: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Status: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Model: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct PsuState {
+    pub PsuId: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
     pub AdminState: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub ModelNum: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub SerialNum: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Vin: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Vout: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Iin: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Iout: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Pin: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Pout: i32,
+}
+
+// temperature and voltage sensors, only one of the current readings is set
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct SensorState {
+    pub Name: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub CurrentTemperature: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub CurrentVoltage: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub HigherAlarmThreshold: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub HigherWarningThreshold: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub LowerWarningThreshold: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub LowerAlarmThreshold: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct PlatformState {
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub ProductName: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub PlatformName: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Manufacturer: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Vendor: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub SerialNum: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Release: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Version: String,
+}
+
+// lane readings are left out for lanes the module does not have
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct QsfpState {
+    pub QsfpId: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Present: bool,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub VendorName: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub VendorPartNumber: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub VendorSerialNumber: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Wavelength: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Temperature: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub SupplyVoltage: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RX1Power: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RX2Power: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RX3Power: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RX4Power: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TX1Power: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TX2Power: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TX3Power: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TX4Power: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TX1Bias: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TX2Bias: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TX3Bias: Option<f64>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TX4Bias: Option<f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct SfpState {
+    pub SfpId: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub SfpPresent: bool,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub VendorName: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub VendorPN: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub VendorSN: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Wavelength: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Temperature: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub SupplyVoltage: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RXPower: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TXPower: f64,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TXBias: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct DaemonState {
+    pub Name: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Enable: bool,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub State: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Reason: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub StartTime: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RestartCount: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RestartReason: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct SystemStatusState {
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Name: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Ready: bool,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Reason: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub UpTime: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub FlexDaemons: Vec<DaemonState>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct RepoState {
+    pub Name: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Sha1: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Branch: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Time: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct SystemSwVersionState {
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub FlexswitchVersion: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Repos: Vec<RepoState>,
 }
 
-#[derive(Clone, RustcEncodable)]
+#[derive(Clone, Serialize, Deserialize)]
 #[allow(non_snake_case)]
 pub struct Vlan {
     pub VlanId: i32,
     pub UntagIntfList: Vec<String>,
 }
 
-#[derive(Clone, RustcEncodable)]
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct StpBridgeInstance {
+    pub Vlan: i32,
+    pub Priority: i32,
+    pub ForceVersion: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct StpPort {
+    pub Vlan: i32,
+    pub IntfRef: String,
+    pub Enable: bool,
+    pub AdminEdgePort: bool,
+    pub BpduGuard: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct LogicalIntf {
+    pub Name: String,
+    pub Type: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 #[allow(non_snake_case)]
 pub struct IPv4Intf {
     pub IntfRef: String,
     pub IpAddr: String,
 }
 
-pub fn reset_and_initalize(connect_string: &str, config_file: &str) {
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct SubIPv4Intf {
+    pub IntfRef: String,
+    pub IpAddr: String,
+    pub VlanId: i32,
+    pub Enable: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct IPv6Intf {
+    pub IntfRef: String,
+    pub IpAddr: String,
+    pub LinkIp: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct SubIPv6Intf {
+    pub IntfRef: String,
+    pub IpAddr: String,
+    pub VlanId: i32,
+    pub LinkIp: bool,
+    pub Enable: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct VrrpIntf {
+    pub IntfRef: String,
+    pub VRID: i32,
+    pub VirtualIPv4Addr: String,
+    pub Priority: i32,
+    pub PreemptMode: bool,
+    pub AdvertisementInterval: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct DhcpRelayGlobal {
+    pub Vrf: String,
+    pub Enable: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct DhcpRelayIntf {
+    pub IntfRef: String,
+    pub Enable: bool,
+    pub ServerIp: Vec<String>,
+}
+
+// the switch is ready once SystemStatus reports so and every enabled daemon is up,
+// probes that get no answer are cut off at the timeout
+pub fn wait_until_ready(connect_string: &str, timeout: Duration) -> Result<(), String> {
+    let started = Instant::now();
+
+    loop {
+        let remaining = timeout.checked_sub(started.elapsed()).unwrap_or_default();
+        let reason = match check_ready(&client_with_timeout(remaining), connect_string) {
+            Ok(()) => return Ok(()),
+            Err(reason) => reason,
+        };
+
+        let remaining = timeout.checked_sub(started.elapsed()).unwrap_or_default();
+        if remaining == Duration::from_secs(0) {
+            return Err(format!("switch {} not ready after {}s, {}", connect_string, timeout.as_secs(), reason));
+        }
+        thread::sleep(cmp::min(remaining, Duration::from_secs(1)));
+    }
+}
+
+// why the switch is not ready yet, daemons that can't be read count as not ready
+fn check_ready(client: &Client, connect_string: &str) -> Result<(), String> {
+    let address = format!("http://{}/public/v1/state/SystemStatus", connect_string);
+    let system = send_state_request(client, &address)
+        .and_then(|body| decode_body::<ObjectEnvelope<SystemStatusState>>(&body))
+        .map_err(|err| format!("system status not available, {}", err))?
+        .object;
+    if !system.Ready {
+        return Err(format!("system not ready: {}", system.Reason));
+    }
+
+    let daemons: Vec<DaemonState> = get_state_with(client, connect_string, "DaemonStates")
+        .map_err(|err| format!("daemon states not available, {}", err))?;
+    match daemons.iter().find(|daemon| daemon.Enable && daemon.State.to_uppercase() != "UP") {
+        Some(daemon) => Err(format!("daemon {} is {}", daemon.Name, daemon.State)),
+        None => Ok(()),
+    }
+}
+
+// hyper waits for a response forever by default, a zero timeout would mean the same
+fn client_with_timeout(timeout: Duration) -> Client {
+    let timeout = cmp::max(timeout, Duration::from_millis(1));
+    let mut client = Client::new();
+    client.set_read_timeout(Some(timeout));
+    client.set_write_timeout(Some(timeout));
+    client
+}
+
+pub fn reset_and_initalize_when_ready(connect_string: &str, config_file: &str, timeout: Duration) -> Result<(), String> {
+    wait_until_ready(connect_string, timeout)?;
+    reset_and_initalize(connect_string, config_file);
+    Ok(())
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct ConfigFile {
+    pub FileName: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct Daemon {
+    pub Name: String,
+    pub Op: String,
+}
+
+pub fn reset_config(connect_string: &str) -> Result<(), String> {
     let client = Client::new();
+    let address = format!("http://{}/public/v1/action/ResetConfig", connect_string);
+    check_action_response("ResetConfig", client.post(&address).send())
+}
 
-    let reset_config = format!("http://{}/public/v1/action/ResetConfig", connect_string);
-    log_request_error!(client.post(&reset_config).send());
+// an empty file name stores to, or applies from, the switch's startup-config
+pub fn save_config(connect_string: &str, file_name: &str) -> Result<(), String> {
+    post_action_object(connect_string, "SaveConfig", &config_file_action(file_name))
+}
 
-    if config_file.is_empty() {
-        return;
+pub fn apply_config(connect_string: &str, file_name: &str) -> Result<(), String> {
+    post_action_object(connect_string, "ApplyConfig", &config_file_action(file_name))
+}
+
+// unlike apply_config, objects already present on the switch are overwritten
+pub fn force_apply_config(connect_string: &str, file_name: &str) -> Result<(), String> {
+    post_action_object(connect_string, "ForceApplyConfig", &config_file_action(file_name))
+}
+
+pub fn restart_daemon(connect_string: &str, name: &str) -> Result<(), String> {
+    let daemon = Daemon {
+        Name: name.to_string(),
+        Op: "restart".to_string(),
+    };
+
+    post_action_object(connect_string, "Daemon", &daemon)
+}
+
+fn config_file_action(file_name: &str) -> ConfigFile {
+    ConfigFile {
+        FileName: match file_name.is_empty() {
+            true => "startup-config".to_string(),
+            false => file_name.to_string(),
+        },
+    }
+}
+
+pub fn reset_and_initalize(connect_string: &str, config_file: &str) {
+    let mut initialization = Initialization::new(config_file);
+
+    loop {
+        match initialization.next_step() {
+            InitStep::ResetConfig => {
+                if let Err(err) = reset_config(connect_string) {
+                    println!("error {}", err);
+                }
+            }
+            InitStep::GetPortNames => initialization.port_names = get_port_names(connect_string),
+            InitStep::Send(requests) => send_config_requests(connect_string, requests),
+            InitStep::Done => return,
+        }
+    }
+}
+
+// the steps of reset_and_initalize, shared with async_client, the caller
+// performs each step and stores the port names when asked for them
+struct Initialization {
+    config: Option<Yaml>,
+    stage: InitStage,
+    port_names: Vec<String>,
+}
+
+enum InitStage {
+    Reset,
+    Ports,
+    PortNames,
+    Interfaces,
+    Done,
+}
+
+enum InitStep {
+    ResetConfig,
+    GetPortNames,
+    Send(Vec<ConfigRequest>),
+    Done,
+}
+
+impl Initialization {
+    // an empty config_file only resets the switch
+    fn new(config_file: &str) -> Initialization {
+        Initialization {
+            config: match config_file.is_empty() {
+                true => None,
+                false => Some(read_config_file(config_file)),
+            },
+            stage: InitStage::Reset,
+            port_names: vec![],
+        }
+    }
+
+    fn next_step(&mut self) -> InitStep {
+        let config = match self.config {
+            Some(ref config) => config,
+            None => {
+                return match self.stage {
+                    InitStage::Reset => {
+                        self.stage = InitStage::Done;
+                        InitStep::ResetConfig
+                    }
+                    _ => InitStep::Done,
+                }
+            }
+        };
+
+        match self.stage {
+            InitStage::Reset => {
+                self.stage = InitStage::Ports;
+                InitStep::ResetConfig
+            }
+            InitStage::Ports => {
+                self.stage = InitStage::PortNames;
+                InitStep::Send(read_port_requests(config))
+            }
+            // port names are only known once the breakout modes are applied
+            InitStage::PortNames => {
+                self.stage = InitStage::Interfaces;
+                match read_interface_ports(config).is_empty() {
+                    true => self.next_step(),
+                    false => InitStep::GetPortNames,
+                }
+            }
+            InitStage::Interfaces => {
+                self.stage = InitStage::Done;
+                InitStep::Send(read_interface_requests(config, &self.port_names))
+            }
+            InitStage::Done => InitStep::Done,
+        }
+    }
+}
+
+enum ConfigMethod {
+    Post,
+    Patch,
+}
+
+// one config change sent during initialization
+struct ConfigRequest {
+    method: ConfigMethod,
+    object_name: &'static str,
+    data: String,
+}
+
+fn config_request<T: Serialize>(method: ConfigMethod, object_name: &'static str, object: &T) -> ConfigRequest {
+    ConfigRequest {
+        method,
+        object_name,
+        data: serde_json::to_string(object).unwrap(),
+    }
+}
+
+fn send_config_requests(connect_string: &str, requests: Vec<ConfigRequest>) {
+    let client = Client::new();
+
+    for request in requests {
+        let address = format!("http://{}/public/v1/config/{}", connect_string, request.object_name);
+        let builder = match request.method {
+            ConfigMethod::Post => client.post(&address),
+            ConfigMethod::Patch => client.patch(&address),
+        };
+        log_request_error!(builder.body(&request.data).header(ContentType::json()).send());
+    }
+}
+
+// ports, vlans and spanning tree, in the order they have to be applied
+fn read_port_requests(config: &Yaml) -> Vec<ConfigRequest> {
+    let mut result = vec![];
+
+    for port in read_ports(config) {
+        result.push(config_request(ConfigMethod::Patch, "Port", &port));
+    }
+
+    for sub_port in read_sub_ports(config) {
+        result.push(config_request(ConfigMethod::Patch, "Port", &sub_port));
+    }
+
+    for vlan in read_vlans(config) {
+        result.push(config_request(ConfigMethod::Post, "Vlan", &vlan));
+    }
+
+    let (stp_bridges, stp_ports) = read_stp(config);
+    for stp_bridge in stp_bridges {
+        result.push(config_request(ConfigMethod::Post, "StpBridgeInstance", &stp_bridge));
+    }
+
+    for stp_port in stp_ports {
+        result.push(config_request(ConfigMethod::Patch, "StpPort", &stp_port));
+    }
+
+    result
+}
+
+// interfaces on ports missing from port_names are skipped
+fn read_interface_requests(config: &Yaml, port_names: &[String]) -> Vec<ConfigRequest> {
+    let mut result = vec![];
+
+    let unknown_ports: Vec<String> =
+        read_interface_ports(config).into_iter().filter(|port| !port_names.contains(port)).collect();
+    for port in &unknown_ports {
+        println!("error unknown port {}, skipping its interfaces", port);
+    }
+
+    // vlan interfaces and loopbacks are never on an unknown port
+    let mut parsed_interfaces = read_interfaces(config);
+    parsed_interfaces.retain(|interface| !unknown_ports.contains(&interface.port_name));
+
+    let interfaces = read_ipv4intf(&parsed_interfaces);
+    let sub_interfaces = read_sub_ipv4intf(&parsed_interfaces);
+    let ipv6_interfaces = read_ipv6intf(&parsed_interfaces);
+    let sub_ipv6_interfaces = read_sub_ipv6intf(&parsed_interfaces);
+    let vrrp_interfaces = read_vrrp_intf(&parsed_interfaces);
+    let dhcp_relay_interfaces = read_dhcp_relay_intf(&parsed_interfaces);
+
+    for logical_interface in read_logical_intf(config) {
+        result.push(config_request(ConfigMethod::Post, "LogicalIntf", &logical_interface));
+    }
+
+    for interface in interfaces {
+        result.push(config_request(ConfigMethod::Post, "IPv4Intf", &interface));
+    }
+
+    for sub_interface in sub_interfaces {
+        result.push(config_request(ConfigMethod::Post, "SubIPv4Intf", &sub_interface));
+    }
+
+    for ipv6_interface in ipv6_interfaces {
+        result.push(config_request(ConfigMethod::Post, "IPv6Intf", &ipv6_interface));
+    }
+
+    for sub_ipv6_interface in sub_ipv6_interfaces {
+        result.push(config_request(ConfigMethod::Post, "SubIPv6Intf", &sub_ipv6_interface));
+    }
+
+    for vrrp_interface in vrrp_interfaces {
+        result.push(config_request(ConfigMethod::Post, "VrrpIntf", &vrrp_interface));
+    }
+
+    if !dhcp_relay_interfaces.is_empty() {
+        let dhcp_relay_global = DhcpRelayGlobal {
+            Vrf: "default".to_string(),
+            Enable: true,
+        };
+        result.push(config_request(ConfigMethod::Patch, "DhcpRelayGlobal", &dhcp_relay_global));
+    }
+
+    for dhcp_relay_interface in dhcp_relay_interfaces {
+        result.push(config_request(ConfigMethod::Post, "DhcpRelayIntf", &dhcp_relay_interface));
+    }
+
+    // rules have to exist before the acls referring to them
+    let acls = read_acls(config);
+    for (_, acl_rules) in &acls {
+        for acl_rule in acl_rules {
+            result.push(config_request(ConfigMethod::Post, "AclRule", acl_rule));
+        }
+    }
+
+    for (acl, _) in &acls {
+        result.push(config_request(ConfigMethod::Post, "Acl", acl));
+    }
+
+    result
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct AclRule {
+    pub RuleName: String,
+    pub SourceMac: String,
+    pub DestMac: String,
+    pub SourceIp: String,
+    pub SourceMask: String,
+    pub DestIp: String,
+    pub DestMask: String,
+    pub Proto: String,
+    pub L4SrcPort: i32,
+    pub L4DstPort: i32,
+    pub Action: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct Acl {
+    pub AclName: String,
+    pub AclType: String,
+    pub IntfList: Vec<String>,
+    pub RuleNameList: Vec<String>,
+    pub Direction: String,
+}
+
+pub fn add_acl_rule(connect_string: &str, acl_rule: &AclRule) -> Option<String> {
+    post_config_object(connect_string, "AclRule", acl_rule)
+}
+
+pub fn update_acl_rule(connect_string: &str, acl_rule: &AclRule) {
+    patch_config_object(connect_string, "AclRule", acl_rule);
+}
+
+pub fn delete_acl_rule(connect_string: &str, rule_name: &str) {
+    let mut acl_rule = empty_acl_rule();
+    acl_rule.RuleName = rule_name.to_string();
+
+    delete_config_object(connect_string, "AclRule", &acl_rule);
+}
+
+pub fn add_acl(connect_string: &str, acl: &Acl) -> Option<String> {
+    post_config_object(connect_string, "Acl", acl)
+}
+
+pub fn update_acl(connect_string: &str, acl: &Acl) {
+    patch_config_object(connect_string, "Acl", acl);
+}
+
+pub fn delete_acl(connect_string: &str, acl_name: &str) {
+    let acl = Acl {
+        AclName: acl_name.to_string(),
+        AclType: "".to_string(),
+        IntfList: vec![],
+        RuleNameList: vec![],
+        Direction: "".to_string(),
+    };
+
+    delete_config_object(connect_string, "Acl", &acl);
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct AclState {
+    pub AclName: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RuleName: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub IntfList: Vec<String>,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub HitCount: i64,
+}
+
+pub fn get_acl_stats(connect_string: &str) -> Vec<AclStat> {
+    let mut result = vec![];
+
+    for acl in get_typed_state_objects::<AclState>(connect_string, "Acls") {
+        result.push(AclStat {
+            acl_name: acl.AclName,
+            rule_name: acl.RuleName,
+            intf_list: acl.IntfList,
+            hit_count: acl.HitCount,
+        })
+    }
+
+    result
+}
+
+fn empty_acl_rule() -> AclRule {
+    AclRule {
+        RuleName: "".to_string(),
+        SourceMac: "".to_string(),
+        DestMac: "".to_string(),
+        SourceIp: "".to_string(),
+        SourceMask: "".to_string(),
+        DestIp: "".to_string(),
+        DestMask: "".to_string(),
+        Proto: "".to_string(),
+        L4SrcPort: 0,
+        L4DstPort: 0,
+        Action: "".to_string(),
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct RouteDistance {
+    pub Protocol: String,
+    pub Distance: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct SourcePolicyList {
+    pub Sources: String,
+    pub Policy: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct BGPRedistribution {
+    pub Vrf: String,
+    pub Redistribution: Vec<SourcePolicyList>,
+}
+
+pub fn set_route_distance(connect_string: &str, protocol: &str, distance: i32) {
+    let route_distance = RouteDistance {
+        Protocol: protocol.to_string(),
+        Distance: distance,
+    };
+
+    patch_config_object(connect_string, "RouteDistance", &route_distance);
+}
+
+// sources like "CONNECTED" or "STATIC", an empty policy redistributes every route of the source
+pub fn set_bgp_redistribution(connect_string: &str, redistribution: &[SourcePolicyList]) {
+    let bgp_redistribution = BGPRedistribution {
+        Vrf: "default".to_string(),
+        Redistribution: redistribution.to_vec(),
+    };
+
+    patch_config_object(connect_string, "BGPGlobal", &bgp_redistribution);
+}
+
+pub fn add_route(connect_string: &str, route_from: &str, route_to: &str) -> Option<String> {
+    match static_route(route_from, Some(route_to)) {
+        Ok(route) => post_config_object(connect_string, "IPv4Route", &route),
+        Err(err) => {
+            println!("error {}", err);
+            None
+        }
+    }
+}
+
+// the route is tracked through a BFD session towards its next hop, routes over the same next hop share
+// the session, so an existing one is reused and a new one is removed again if the route can't be added
+pub fn add_route_with_bfd(connect_string: &str,
+                          route_from: &str,
+                          route_to: &str,
+                          bfd_param_name: &str)
+                          -> Result<String, String> {
+    let route = static_route(route_from, Some(route_to))?;
+    let bfd_session = bfd_session(route_to, "", bfd_param_name);
+    let session_created = match get_object_id(connect_string, &bfd_session)? {
+        Some(_) => false,
+        None => {
+            create(connect_string, &bfd_session).map_err(|err| format!("BFD session to {} failed: {}", route_to, err))?;
+            true
+        }
+    };
+
+    let result = create(connect_string, &route);
+    if result.is_err() && session_created {
+        if let Err(err) = delete(connect_string, &bfd_session) {
+            println!("error {}", err);
+        }
+    }
+
+    result
+}
+
+// deletes a route added with add_route_with_bfd, its BFD session goes too once no other route uses the next hop
+pub fn delete_route_with_bfd(connect_string: &str, route_from: &str, route_to: &str) -> Result<(), String> {
+    match get_object_id(connect_string, &static_route(route_from, None)?)? {
+        Some(object_id) => delete_by_id::<IPv4Route>(connect_string, &object_id)?,
+        None => return Err(format!("unknown route {}", route_from)),
+    }
+
+    let next_hop_in_use = get_all::<IPv4Route>(connect_string)?
+        .iter()
+        .any(|route| route.NextHop.iter().any(|next_hop| next_hop.NextHopIp == route_to));
+    match next_hop_in_use {
+        true => Ok(()),
+        false => delete(connect_string, &bfd_session(route_to, "", "")),
+    }
+}
+
+pub fn delete_route(connect_string: &str, route_from: &str) {
+    match static_route(route_from, None) {
+        Ok(route) => delete_config_object_by_id(connect_string, &route),
+        Err(err) => println!("error {}", err),
+    }
+}
+
+// a route without next hop is enough to address it for deletion
+fn static_route(route_from: &str, route_to: Option<&str>) -> Result<IPv4Route, String> {
+    let (ip, mask) = split_address_into_ip_and_mask(route_from)?;
+    Ok(IPv4Route {
+        DestinationNw: ip,
+        NetworkMask: mask,
+        Protocol: "STATIC".to_string(),
+        NextHop: route_to.into_iter().map(|route_to| NextHopInfo { NextHopIp: route_to.to_string() }).collect(),
+    })
+}
+
+pub fn delete_route_by_id(connect_string: &str, object_id: &str) {
+    if let Err(err) = delete_by_id::<IPv4Route>(connect_string, object_id) {
+        println!("error {}", err);
+    }
+}
+
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct SystemParam {
+    pub Vrf: String,
+    pub Hostname: String,
+    pub MgmtIp: String,
+    pub Description: String,
+}
+
+// SystemParam is a singleton, unchanged fields have to be passed with their current values
+pub fn set_system_param(connect_string: &str, system_param: &SystemParam) {
+    patch_config_object(connect_string, "SystemParam", system_param);
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct LLDPGlobal {
+    pub Vrf: String,
+    pub Enable: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct LLDPIntf {
+    pub IntfRef: String,
+    pub Enable: bool,
+}
+
+pub fn set_lldp_global(connect_string: &str, enable: bool) {
+    let lldp_global = LLDPGlobal {
+        Vrf: "default".to_string(),
+        Enable: enable,
+    };
+
+    patch_config_object(connect_string, "LLDPGlobal", &lldp_global);
+}
+
+pub fn set_lldp_interface(connect_string: &str, intf_ref: &str, enable: bool) {
+    let lldp_intf = LLDPIntf {
+        IntfRef: intf_ref.to_string(),
+        Enable: enable,
+    };
+
+    patch_config_object(connect_string, "LLDPIntf", &lldp_intf);
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct LLDPIntfState {
+    pub IntfRef: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub IfIndex: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub PeerMac: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub PeerPort: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub PeerHostName: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub PeerMgmtAddr: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub EnabledCapabilities: String,
+}
+
+pub fn get_lldp_neighbors(connect_string: &str) -> Vec<LLDPNeighbor> {
+    let mut result = vec![];
+
+    for lldp_intf in get_typed_state_objects::<LLDPIntfState>(connect_string, "LLDPIntfs") {
+        // ports without a neighbor are reported too, skip them
+        if lldp_intf.PeerMac.is_empty() {
+            continue;
+        }
+
+        result.push(LLDPNeighbor {
+            port_id: lldp_intf.IfIndex,
+            intf_ref: lldp_intf.IntfRef,
+            peer_chassis_id: lldp_intf.PeerMac,
+            peer_port_id: lldp_intf.PeerPort,
+            peer_system_name: lldp_intf.PeerHostName,
+            peer_mgmt_addr: lldp_intf.PeerMgmtAddr,
+            peer_capabilities: lldp_intf.EnabledCapabilities,
+        })
+    }
+
+    result
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct BfdGlobal {
+    pub Vrf: String,
+    pub Enable: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct BfdSessionParam {
+    pub Name: String,
+    pub LocalMultiplier: i32,
+    pub DesiredMinTxInterval: i32,
+    pub RequiredMinRxInterval: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct BfdSession {
+    pub IpAddr: String,
+    pub ParamName: String,
+    pub Interface: String,
+    pub Owner: String,
+}
+
+pub fn set_bfd_global(connect_string: &str, enable: bool) {
+    let bfd_global = BfdGlobal {
+        Vrf: "default".to_string(),
+        Enable: enable,
+    };
+
+    patch_config_object(connect_string, "BfdGlobal", &bfd_global);
+}
+
+// intervals are given in microseconds
+pub fn add_bfd_session_param(connect_string: &str,
+                             name: &str,
+                             local_multiplier: i32,
+                             desired_min_tx_interval: i32,
+                             required_min_rx_interval: i32) -> Option<String> {
+    let bfd_session_param = BfdSessionParam {
+        Name: name.to_string(),
+        LocalMultiplier: local_multiplier,
+        DesiredMinTxInterval: desired_min_tx_interval,
+        RequiredMinRxInterval: required_min_rx_interval,
+    };
+
+    post_config_object(connect_string, "BfdSessionParam", &bfd_session_param)
+}
+
+pub fn delete_bfd_session_param(connect_string: &str, name: &str) {
+    let bfd_session_param = BfdSessionParam {
+        Name: name.to_string(),
+        LocalMultiplier: 0,
+        DesiredMinTxInterval: 0,
+        RequiredMinRxInterval: 0,
+    };
+
+    delete_config_object(connect_string, "BfdSessionParam", &bfd_session_param);
+}
+
+// an empty interface lets the switch pick the interface towards the destination
+pub fn add_bfd_session(connect_string: &str, destination: &str, interface: &str, param_name: &str) -> Option<String> {
+    post_config_object(connect_string, "BfdSession", &bfd_session(destination, interface, param_name))
+}
+
+pub fn delete_bfd_session(connect_string: &str, destination: &str) {
+    delete_config_object(connect_string, "BfdSession", &bfd_session(destination, "", ""));
+}
+
+fn bfd_session(destination: &str, interface: &str, param_name: &str) -> BfdSession {
+    BfdSession {
+        IpAddr: destination.to_string(),
+        ParamName: param_name.to_string(),
+        Interface: interface.to_string(),
+        Owner: "user".to_string(),
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct BfdSessionState {
+    pub IpAddr: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub IfName: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub ParamName: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub SessionState: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RemoteSessionState: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub DetectionMultiplier: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub DesiredMinTxInterval: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RequiredMinRxInterval: i32,
+}
+
+pub fn get_bfd_sessions(connect_string: &str) -> Vec<BfdSessionStat> {
+    let mut result = vec![];
+
+    for session in get_typed_state_objects::<BfdSessionState>(connect_string, "BfdSessions") {
+        result.push(BfdSessionStat {
+            ip_addr: session.IpAddr,
+            if_name: session.IfName,
+            param_name: session.ParamName,
+            session_state: session.SessionState,
+            remote_session_state: session.RemoteSessionState,
+            detection_multiplier: session.DetectionMultiplier,
+            desired_min_tx_interval: session.DesiredMinTxInterval,
+            required_min_rx_interval: session.RequiredMinRxInterval,
+        })
+    }
+
+    result
+}
+
+pub fn add_vlan(connect_string: &str, vlan_id: i32, untagged_ports: &[String]) -> Option<String> {
+    let vlan = Vlan {
+        VlanId: vlan_id,
+        UntagIntfList: untagged_ports.to_vec(),
+    };
+
+    post_config_object(connect_string, "Vlan", &vlan)
+}
+
+pub fn update_vlan(connect_string: &str, vlan: &Vlan) {
+    match get_object_id(connect_string, vlan) {
+        Ok(Some(object_id)) => {
+            if let Err(err) = update_by_id(connect_string, &object_id, vlan) {
+                println!("error {}", err);
+            }
+        }
+        Ok(None) => println!("error unknown vlan {}", vlan.VlanId),
+        Err(err) => println!("error {}", err),
+    }
+}
+
+pub fn delete_vlan(connect_string: &str, vlan_id: i32) {
+    let vlan = Vlan {
+        VlanId: vlan_id,
+        UntagIntfList: vec![],
+    };
+
+    delete_config_object_by_id(connect_string, &vlan);
+}
+
+pub fn add_loopback(connect_string: &str, name: &str) -> Option<String> {
+    let logical_intf = LogicalIntf {
+        Name: name.to_string(),
+        Type: "Loopback".to_string(),
+    };
+
+    post_config_object(connect_string, "LogicalIntf", &logical_intf)
+}
+
+pub fn delete_loopback(connect_string: &str, name: &str) {
+    let logical_intf = LogicalIntf {
+        Name: name.to_string(),
+        Type: "Loopback".to_string(),
+    };
+
+    delete_config_object(connect_string, "LogicalIntf", &logical_intf);
+}
+
+// intf_ref is a vlan like "vlan100", a port or a loopback, the address includes the prefix length
+pub fn add_ipv4_interface(connect_string: &str, intf_ref: &str, address: &str) -> Option<String> {
+    let ipv4_intf = IPv4Intf {
+        IntfRef: intf_ref.to_string(),
+        IpAddr: address.to_string(),
+    };
+
+    post_config_object(connect_string, "IPv4Intf", &ipv4_intf)
+}
+
+pub fn delete_ipv4_interface(connect_string: &str, intf_ref: &str) {
+    let ipv4_intf = IPv4Intf {
+        IntfRef: intf_ref.to_string(),
+        IpAddr: "".to_string(),
+    };
+
+    delete_config_object_by_id(connect_string, &ipv4_intf);
+}
+
+// port is a front panel port like "fpPort3" or a sub-interface like "fpPort3.200"
+pub fn add_port_ipv4_interface(connect_string: &str, port: &str, address: &str) -> Result<String, String> {
+    let (port_name, vlan_id) = split_sub_intf_ref(port)?;
+    if !get_port_names(connect_string).contains(&port_name) {
+        return Err(format!("unknown port {}", port_name));
+    }
+
+    let object_id = match vlan_id {
+        Some(vlan_id) => {
+            let sub_ipv4_intf = SubIPv4Intf {
+                IntfRef: port_name,
+                IpAddr: address.to_string(),
+                VlanId: vlan_id,
+                Enable: true,
+            };
+            post_config_object(connect_string, "SubIPv4Intf", &sub_ipv4_intf)
+        }
+        None => add_ipv4_interface(connect_string, &port_name, address),
+    };
+
+    object_id.ok_or(format!("creating interface {} on {} failed", address, port))
+}
+
+pub fn delete_port_ipv4_interface(connect_string: &str, port: &str, address: &str) {
+    let (port_name, vlan_id) = match split_sub_intf_ref(port) {
+        Ok(sub_intf_ref) => sub_intf_ref,
+        Err(err) => {
+            println!("error {}", err);
+            return;
+        }
+    };
+
+    match vlan_id {
+        Some(vlan_id) => {
+            let sub_ipv4_intf = SubIPv4Intf {
+                IntfRef: port_name,
+                IpAddr: address.to_string(),
+                VlanId: vlan_id,
+                Enable: false,
+            };
+            delete_config_object_by_id(connect_string, &sub_ipv4_intf);
+        }
+        None => delete_ipv4_interface(connect_string, &port_name),
+    }
+}
+
+// link_local lets the switch derive a link-local address next to the global one
+pub fn add_ipv6_interface(connect_string: &str, intf_ref: &str, address: &str, link_local: bool) -> Option<String> {
+    let ipv6_intf = IPv6Intf {
+        IntfRef: intf_ref.to_string(),
+        IpAddr: address.to_string(),
+        LinkIp: link_local,
+    };
+
+    post_config_object(connect_string, "IPv6Intf", &ipv6_intf)
+}
+
+pub fn delete_ipv6_interface(connect_string: &str, intf_ref: &str) {
+    let ipv6_intf = IPv6Intf {
+        IntfRef: intf_ref.to_string(),
+        IpAddr: "".to_string(),
+        LinkIp: false,
+    };
+
+    delete_config_object_by_id(connect_string, &ipv6_intf);
+}
+
+// like add_port_ipv4_interface, port is a port name or a "fpPort1.200" sub-interface
+pub fn add_port_ipv6_interface(connect_string: &str, port: &str, address: &str, link_local: bool) -> Result<String, String> {
+    let (port_name, vlan_id) = split_sub_intf_ref(port)?;
+    if !get_port_names(connect_string).contains(&port_name) {
+        return Err(format!("unknown port {}", port_name));
+    }
+
+    let object_id = match vlan_id {
+        Some(vlan_id) => {
+            let sub_ipv6_intf = SubIPv6Intf {
+                IntfRef: port_name,
+                IpAddr: address.to_string(),
+                VlanId: vlan_id,
+                LinkIp: link_local,
+                Enable: true,
+            };
+            post_config_object(connect_string, "SubIPv6Intf", &sub_ipv6_intf)
+        }
+        None => add_ipv6_interface(connect_string, &port_name, address, link_local),
+    };
+
+    object_id.ok_or(format!("creating interface {} on {} failed", address, port))
+}
+
+pub fn delete_port_ipv6_interface(connect_string: &str, port: &str, address: &str) {
+    let (port_name, vlan_id) = match split_sub_intf_ref(port) {
+        Ok(sub_intf_ref) => sub_intf_ref,
+        Err(err) => {
+            println!("error {}", err);
+            return;
+        }
+    };
+
+    match vlan_id {
+        Some(vlan_id) => {
+            let sub_ipv6_intf = SubIPv6Intf {
+                IntfRef: port_name,
+                IpAddr: address.to_string(),
+                VlanId: vlan_id,
+                LinkIp: false,
+                Enable: false,
+            };
+            delete_config_object_by_id(connect_string, &sub_ipv6_intf);
+        }
+        None => delete_ipv6_interface(connect_string, &port_name),
+    }
+}
+
+pub fn add_vrrp_interface(connect_string: &str, vrrp_intf: &VrrpIntf) -> Option<String> {
+    post_config_object(connect_string, "VrrpIntf", vrrp_intf)
+}
+
+pub fn delete_vrrp_interface(connect_string: &str, intf_ref: &str, vrid: i32) {
+    let vrrp_intf = VrrpIntf {
+        IntfRef: intf_ref.to_string(),
+        VRID: vrid,
+        VirtualIPv4Addr: "".to_string(),
+        Priority: 0,
+        PreemptMode: false,
+        AdvertisementInterval: 0,
+    };
+
+    delete_config_object(connect_string, "VrrpIntf", &vrrp_intf);
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct VrrpIntfState {
+    pub IntfRef: String,
+    pub VRID: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub VirtualIPv4Addr: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Priority: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub VrrpState: String,
+}
+
+pub fn get_vrrp_interfaces(connect_string: &str) -> Vec<VrrpStat> {
+    let mut result = vec![];
+
+    for vrrp_intf in get_typed_state_objects::<VrrpIntfState>(connect_string, "VrrpIntfs") {
+        result.push(VrrpStat {
+            intf_ref: vrrp_intf.IntfRef,
+            vrid: vrrp_intf.VRID,
+            virtual_ip: vrrp_intf.VirtualIPv4Addr,
+            priority: vrrp_intf.Priority,
+            state: vrrp_intf.VrrpState,
+        })
+    }
+
+    result
+}
+
+pub fn set_dhcp_relay_global(connect_string: &str, enable: bool) {
+    let dhcp_relay_global = DhcpRelayGlobal {
+        Vrf: "default".to_string(),
+        Enable: enable,
+    };
+
+    patch_config_object(connect_string, "DhcpRelayGlobal", &dhcp_relay_global);
+}
+
+pub fn add_dhcp_relay_interface(connect_string: &str, intf_ref: &str, servers: &[String]) -> Option<String> {
+    let dhcp_relay_intf = DhcpRelayIntf {
+        IntfRef: intf_ref.to_string(),
+        Enable: true,
+        ServerIp: servers.to_vec(),
+    };
+
+    post_config_object(connect_string, "DhcpRelayIntf", &dhcp_relay_intf)
+}
+
+pub fn delete_dhcp_relay_interface(connect_string: &str, intf_ref: &str) {
+    let dhcp_relay_intf = DhcpRelayIntf {
+        IntfRef: intf_ref.to_string(),
+        Enable: false,
+        ServerIp: vec![],
+    };
+
+    delete_config_object(connect_string, "DhcpRelayIntf", &dhcp_relay_intf);
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct DhcpRelayIntfState {
+    pub IntfRef: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TotalDrops: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TotalDhcpClientRx: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TotalDhcpClientTx: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TotalDhcpServerRx: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TotalDhcpServerTx: i32,
+}
+
+pub fn get_dhcp_relay_stats(connect_string: &str) -> Vec<DhcpRelayStat> {
+    let mut result = vec![];
+
+    for dhcp_relay_intf in get_typed_state_objects::<DhcpRelayIntfState>(connect_string, "DhcpRelayIntfs") {
+        result.push(DhcpRelayStat {
+            intf_ref: dhcp_relay_intf.IntfRef,
+            total_drops: dhcp_relay_intf.TotalDrops,
+            client_rx: dhcp_relay_intf.TotalDhcpClientRx,
+            client_tx: dhcp_relay_intf.TotalDhcpClientTx,
+            server_rx: dhcp_relay_intf.TotalDhcpServerRx,
+            server_tx: dhcp_relay_intf.TotalDhcpServerTx,
+        })
+    }
+
+    result
+}
+
+pub fn add_stp_bridge_instance(connect_string: &str, stp_bridge: &StpBridgeInstance) -> Option<String> {
+    post_config_object(connect_string, "StpBridgeInstance", stp_bridge)
+}
+
+pub fn delete_stp_bridge_instance(connect_string: &str, vlan: i32) {
+    let stp_bridge = StpBridgeInstance {
+        Vlan: vlan,
+        Priority: 0,
+        ForceVersion: 0,
+    };
+
+    delete_config_object(connect_string, "StpBridgeInstance", &stp_bridge);
+}
+
+pub fn set_stp_port(connect_string: &str, stp_port: &StpPort) {
+    patch_config_object(connect_string, "StpPort", stp_port);
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct StpBridgeInstanceState {
+    pub Vlan: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Address: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub DesignatedRoot: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RootPort: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub RootPathCost: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub TopologyChanges: i32,
+}
+
+pub fn get_stp_bridges(connect_string: &str) -> Vec<StpBridgeStat> {
+    let mut result = vec![];
+
+    for stp_bridge in get_typed_state_objects::<StpBridgeInstanceState>(connect_string, "StpBridgeInstances") {
+        result.push(StpBridgeStat {
+            vlan: stp_bridge.Vlan,
+            bridge_id: stp_bridge.Address,
+            designated_root: stp_bridge.DesignatedRoot,
+            root_port: stp_bridge.RootPort,
+            root_path_cost: stp_bridge.RootPathCost,
+            topology_changes: stp_bridge.TopologyChanges,
+        })
+    }
+
+    result
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct StpPortState {
+    pub IntfRef: String,
+    pub Vlan: i32,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub Role: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub State: String,
+    #[serde(default, deserialize_with = "envelope::null_as_default")]
+    pub DesignatedRoot: String,
+}
+
+pub fn get_stp_ports(connect_string: &str) -> Vec<StpPortStat> {
+    let mut result = vec![];
+
+    for stp_port in get_typed_state_objects::<StpPortState>(connect_string, "StpPorts") {
+        result.push(StpPortStat {
+            intf_ref: stp_port.IntfRef,
+            vlan: stp_port.Vlan,
+            role: stp_port.Role,
+            state: stp_port.State,
+            designated_root: stp_port.DesignatedRoot,
+        })
+    }
+
+    result
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct PolicyPrefix {
+    pub Prefix: String,
+    pub MaskLengthRange: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct PolicyPrefixSet {
+    pub Name: String,
+    pub PrefixList: Vec<PolicyPrefix>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct PolicyCondition {
+    pub Name: String,
+    pub ConditionType: String,
+    pub Protocol: String,
+    pub PrefixSet: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct PolicySetAction {
+    pub Attr: String,
+    pub Value: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct PolicyStmt {
+    pub Name: String,
+    pub MatchConditions: String,
+    pub Conditions: Vec<String>,
+    pub Action: String,
+    pub SetActions: Vec<PolicySetAction>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct PolicyDefinitionStmtPriority {
+    pub Priority: i32,
+    pub Statement: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct PolicyDefinition {
+    pub Name: String,
+    pub Priority: i32,
+    pub MatchType: String,
+    pub PolicyType: String,
+    pub StatementList: Vec<PolicyDefinitionStmtPriority>,
+}
+
+pub fn add_policy_prefix_set(connect_string: &str, prefix_set: &PolicyPrefixSet) -> Option<String> {
+    post_config_object(connect_string, "PolicyPrefixSet", prefix_set)
+}
+
+pub fn update_policy_prefix_set(connect_string: &str, prefix_set: &PolicyPrefixSet) {
+    patch_config_object(connect_string, "PolicyPrefixSet", prefix_set);
+}
+
+pub fn delete_policy_prefix_set(connect_string: &str, name: &str) {
+    let prefix_set = PolicyPrefixSet {
+        Name: name.to_string(),
+        PrefixList: vec![],
+    };
+
+    delete_config_object(connect_string, "PolicyPrefixSet", &prefix_set);
+}
+
+pub fn add_policy_condition(connect_string: &str, condition: &PolicyCondition) -> Option<String> {
+    post_config_object(connect_string, "PolicyCondition", condition)
+}
+
+pub fn update_policy_condition(connect_string: &str, condition: &PolicyCondition) {
+    patch_config_object(connect_string, "PolicyCondition", condition);
+}
+
+pub fn delete_policy_condition(connect_string: &str, name: &str) {
+    let condition = PolicyCondition {
+        Name: name.to_string(),
+        ConditionType: "".to_string(),
+        Protocol: "".to_string(),
+        PrefixSet: "".to_string(),
+    };
+
+    delete_config_object(connect_string, "PolicyCondition", &condition);
+}
+
+pub fn add_policy_stmt(connect_string: &str, stmt: &PolicyStmt) -> Option<String> {
+    post_config_object(connect_string, "PolicyStmt", stmt)
+}
+
+pub fn update_policy_stmt(connect_string: &str, stmt: &PolicyStmt) {
+    patch_config_object(connect_string, "PolicyStmt", stmt);
+}
+
+pub fn delete_policy_stmt(connect_string: &str, name: &str) {
+    let stmt = PolicyStmt {
+        Name: name.to_string(),
+        MatchConditions: "".to_string(),
+        Conditions: vec![],
+        Action: "".to_string(),
+        SetActions: vec![],
+    };
+
+    delete_config_object(connect_string, "PolicyStmt", &stmt);
+}
+
+pub fn add_policy_definition(connect_string: &str, definition: &PolicyDefinition) -> Option<String> {
+    post_config_object(connect_string, "PolicyDefinition", definition)
+}
+
+pub fn update_policy_definition(connect_string: &str, definition: &PolicyDefinition) {
+    patch_config_object(connect_string, "PolicyDefinition", definition);
+}
+
+pub fn delete_policy_definition(connect_string: &str, name: &str) {
+    let definition = PolicyDefinition {
+        Name: name.to_string(),
+        Priority: 0,
+        MatchType: "".to_string(),
+        PolicyType: "".to_string(),
+        StatementList: vec![],
+    };
+
+    delete_config_object(connect_string, "PolicyDefinition", &definition);
+}
+
+// All FlexSwitch objects making up one policy, in the order they have to be created.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Policy {
+    pub prefix_sets: Vec<PolicyPrefixSet>,
+    pub conditions: Vec<PolicyCondition>,
+    pub statements: Vec<PolicyStmt>,
+    pub definition: PolicyDefinition,
+}
+
+// Builds a policy from rules like "permit 10.0.0.0/8 le 24, set local-pref 200",
+// every rule becomes its own statement, evaluated in the given order.
+pub fn build_policy(name: &str, priority: i32, rules: &[&str]) -> Result<Policy, String> {
+    let mut policy = Policy {
+        prefix_sets: vec![],
+        conditions: vec![],
+        statements: vec![],
+        definition: PolicyDefinition {
+            Name: name.to_string(),
+            Priority: priority,
+            MatchType: "all".to_string(),
+            PolicyType: "ALL".to_string(),
+            StatementList: vec![],
+        },
+    };
+
+    for (index, rule) in rules.iter().enumerate() {
+        let stmt_name = format!("{}_{}", name, index + 1);
+        let mut clauses = rule.split(',').map(|clause| clause.trim());
+
+        let match_clause: Vec<&str> = clauses.next().unwrap().split_whitespace().collect();
+        let action = match match_clause.first() {
+            Some(&"permit") => "permit",
+            Some(&"deny") => "deny",
+            _ => return Err(format!("rule '{}' has to start with permit or deny", rule)),
+        };
+
+        let mut conditions = vec![];
+        if match_clause.len() > 1 {
+            let prefix = parse_policy_prefix(&match_clause[1..]).map_err(|err| format!("rule '{}': {}", rule, err))?;
+            let prefix_set_name = format!("{}_prefixes", stmt_name);
+            let condition_name = format!("{}_match", stmt_name);
+
+            policy.prefix_sets.push(PolicyPrefixSet {
+                Name: prefix_set_name.clone(),
+                PrefixList: vec![prefix],
+            });
+            policy.conditions.push(PolicyCondition {
+                Name: condition_name.clone(),
+                ConditionType: "MatchDstIpPrefix".to_string(),
+                Protocol: "".to_string(),
+                PrefixSet: prefix_set_name,
+            });
+            conditions.push(condition_name);
+        }
+
+        let mut set_actions = vec![];
+        for clause in clauses {
+            let words: Vec<&str> = clause.split_whitespace().collect();
+            if words.len() != 3 || words[0] != "set" {
+                return Err(format!("rule '{}': expected 'set <attribute> <value>' but got '{}'", rule, clause));
+            }
+            let attr = match words[1] {
+                "local-pref" => "LocalPref",
+                "med" => "MED",
+                "community" => "Community",
+                "as-path-prepend" => "ASPathPrepend",
+                "next-hop" => "NextHop",
+                _ => return Err(format!("rule '{}': unknown attribute '{}'", rule, words[1])),
+            };
+            set_actions.push(PolicySetAction {
+                Attr: attr.to_string(),
+                Value: words[2].to_string(),
+            });
+        }
+
+        policy.statements.push(PolicyStmt {
+            Name: stmt_name.clone(),
+            MatchConditions: "all".to_string(),
+            Conditions: conditions,
+            Action: action.to_string(),
+            SetActions: set_actions,
+        });
+        policy.definition.StatementList.push(PolicyDefinitionStmtPriority {
+            Priority: (index + 1) as i32,
+            Statement: stmt_name,
+        });
+    }
+
+    Ok(policy)
+}
+
+pub fn add_policy(connect_string: &str, policy: &Policy) {
+    for prefix_set in &policy.prefix_sets {
+        add_policy_prefix_set(connect_string, prefix_set);
+    }
+
+    for condition in &policy.conditions {
+        add_policy_condition(connect_string, condition);
+    }
+
+    for statement in &policy.statements {
+        add_policy_stmt(connect_string, statement);
+    }
+
+    add_policy_definition(connect_string, &policy.definition);
+}
+
+pub fn delete_policy(connect_string: &str, policy: &Policy) {
+    delete_policy_definition(connect_string, &policy.definition.Name);
+
+    for statement in &policy.statements {
+        delete_policy_stmt(connect_string, &statement.Name);
+    }
+
+    for condition in &policy.conditions {
+        delete_policy_condition(connect_string, &condition.Name);
+    }
+
+    for prefix_set in &policy.prefix_sets {
+        delete_policy_prefix_set(connect_string, &prefix_set.Name);
+    }
+}
+
+fn get_sensor_readings(connect_string: &str, object_name: &str, current: fn(&SensorState) -> f64) -> Vec<SensorReading> {
+    let mut result = vec![];
+
+    for sensor in get_typed_state_objects::<SensorState>(connect_string, object_name) {
+        result.push(SensorReading {
+            current: current(&sensor),
+            name: sensor.Name,
+            higher_alarm_threshold: sensor.HigherAlarmThreshold,
+            higher_warning_threshold: sensor.HigherWarningThreshold,
+            lower_warning_threshold: sensor.LowerWarningThreshold,
+            lower_alarm_threshold: sensor.LowerAlarmThreshold,
+        })
+    }
+
+    result
+}
+
+fn get_port_indexes(connect_string: &str) -> Vec<(String, i32)> {
+    get_typed_state_objects::<PortState>(connect_string, "Ports")
+        .into_iter()
+        .map(|port| (port.IntfRef, port.IfIndex))
+        .collect()
+}
+
+// transceiver N sits in front panel port fpPortN, breakouts are named fpPortNs1 .. fpPortNs4
+fn transceiver_port_ids(port_indexes: &[(String, i32)], transceiver_id: i32) -> Vec<i32> {
+    let port_name = format!("fpPort{}", transceiver_id);
+    let breakout_prefix = format!("fpPort{}s", transceiver_id);
+
+    port_indexes.iter()
+        .filter(|&(name, _)| *name == port_name || name.starts_with(&breakout_prefix))
+        .map(|&(_, index)| index)
+        .collect()
+}
+
+fn get_port_names(connect_string: &str) -> Vec<String> {
+    get_port_indexes(connect_string).into_iter().map(|(name, _)| name).collect()
+}
+
+fn read_daemon_stat(daemon: DaemonState) -> DaemonStat {
+    DaemonStat {
+        name: daemon.Name,
+        enabled: daemon.Enable,
+        state: daemon.State,
+        reason: daemon.Reason,
+        start_time: daemon.StartTime,
+        restart_count: daemon.RestartCount,
+        restart_reason: daemon.RestartReason,
+    }
+}
+
+// singleton state objects like SystemStatus are returned as one "Object" instead of a list
+fn get_state_object<T: DeserializeOwned>(connect_string: &str, object_name: &str) -> Option<T> {
+    let address = format!("http://{}/public/v1/state/{}", connect_string, object_name);
+
+    match get_state_body(&address).and_then(|body| decode_body::<ObjectEnvelope<T>>(&body)) {
+        Ok(envelope) => Some(envelope.object),
+        Err(err) => {
+            println!("error {}", err);
+            None
+        }
+    }
+}
+
+fn get_typed_state_objects<T: DeserializeOwned>(connect_string: &str, object_name: &str) -> Vec<T> {
+    match get_state(connect_string, object_name) {
+        Ok(snap_objects) => snap_objects,
+        Err(err) => {
+            println!("error {}", err);
+            vec![]
+        }
+    }
+}
+
+// decodes every object of a bulk state GET like "Ports" into T, following NextMarker across pages
+pub fn get_state<T: DeserializeOwned>(connect_string: &str, object_name: &str) -> Result<Vec<T>, String> {
+    get_state_with(&Client::new(), connect_string, object_name)
+}
+
+fn get_state_with<T: DeserializeOwned>(client: &Client, connect_string: &str, object_name: &str) -> Result<Vec<T>, String> {
+    let mut result = vec![];
+    let mut marker = 0;
+
+    loop {
+        let address = format!("http://{}/public/v1/state/{}?CurrentMarker={}", connect_string, object_name, marker);
+        let object_list = decode_body::<ObjectList<T>>(&send_state_request(client, &address)?)?;
+
+        result.extend(object_list.objects.into_iter().map(|envelope| envelope.object));
+        if !object_list.more_exist || object_list.next_marker == marker {
+            return Ok(result);
+        }
+        marker = object_list.next_marker;
+    }
+}
+
+fn get_state_body(address: &str) -> Result<String, String> {
+    send_state_request(&Client::new(), address)
+}
+
+fn send_state_request(client: &Client, address: &str) -> Result<String, String> {
+    let mut response = client.get(address).send().map_err(|err| format!("{}: {}", address, err))?;
+
+    let mut body = String::new();
+    response.read_to_string(&mut body).map_err(|err| format!("{}: {}", address, err))?;
+
+    match response.status {
+        StatusCode::Ok => Ok(body),
+        status => Err(format!("{}: {} {}", status, address, body.trim())),
     }
+}
 
-    let config_port = format!("http://{}/public/v1/config/Port", connect_string);
-    let config_vlan = format!("http://{}/public/v1/config/Vlan", connect_string);
-    let config_interface = format!("http://{}/public/v1/config/IPv4Intf", connect_string);
-
-    let config = read_config_file(config_file);
+fn decode_body<T: DeserializeOwned>(body: &str) -> Result<T, String> {
+    serde_json::from_str(body).map_err(|err| format!("invalid response {}: {}", body, err))
+}
 
-    let ports = read_ports(&config);
-    let sub_ports = read_sub_ports(&config);
-    let vlans = read_vlans(&config);
-    let interfaces = read_ipv4intf(&config);
+fn patch_config_object<T: Serialize>(connect_string: &str, object_name: &str, object: &T) {
+    let data = serde_json::to_string(object).unwrap();
+    let client = Client::new();
+    let address = format!("http://{}/public/v1/config/{}", connect_string, object_name);
+    log_request_error!(client.patch(&address).body(&data).header(ContentType::json()).send());
+}
 
-    for port in ports {
-        let data = json::encode(&port).unwrap();
-        log_request_error!(client.patch(&config_port).body(&data).header(ContentType::json()).send());
-    }
+// returns the ObjectId of the created object, None if the switch rejected it
+fn post_config_object<T: Serialize>(connect_string: &str, object_name: &str, object: &T) -> Option<String> {
+    let data = serde_json::to_string(object).unwrap();
+    let client = Client::new();
+    let address = format!("http://{}/public/v1/config/{}", connect_string, object_name);
 
-    for sub_port in sub_ports {
-        let data = json::encode(&sub_port).unwrap();
-        log_request_error!(client.patch(&config_port).body(&data).header(ContentType::json()).send());
-    }
+    let mut response = log_request_error!(client.post(&address).body(&data).header(ContentType::json()).send())?;
 
-    for vlan in vlans {
-        let data = json::encode(&vlan).unwrap();
-        log_request_error!(client.post(&config_vlan).body(&data).header(ContentType::json()).send());
-    }
+    match response.status.to_u16() >= 400 {
+        true => None,
+        false => {
+            let mut body = String::new();
+            if let Err(err) = response.read_to_string(&mut body) {
+                println!("error {}: {}", address, err);
+                return None;
+            }
 
-    for interface in interfaces {
-        let data = json::encode(&interface).unwrap();
-        log_request_error!(client.post(&config_interface).body(&data).header(ContentType::json()).send());
+            decode_body::<CreateResponse>(&body).ok().map(|create_response| create_response.object_id)
+        }
     }
-
 }
 
-#[derive(Clone, RustcEncodable)]
-#[allow(non_snake_case)]
-pub struct NextHopInfo {
-    pub NextHopIp: String,
+fn delete_config_object<T: Serialize>(connect_string: &str, object_name: &str, object: &T) {
+    let data = serde_json::to_string(object).unwrap();
+    let client = Client::new();
+    let address = format!("http://{}/public/v1/config/{}", connect_string, object_name);
+    log_request_error!(client.delete(&address).body(&data).header(ContentType::json()).send());
 }
 
-#[derive(Clone, RustcEncodable)]
-#[allow(non_snake_case)]
-pub struct IPv4Route {
-    pub DestinationNw: String,
-    pub NetworkMask: String,
-    pub Protocol: String,
-    pub NextHop: Vec<NextHopInfo>,
+fn post_action_object<T: Serialize>(connect_string: &str, action_name: &str, object: &T) -> Result<(), String> {
+    let data = serde_json::to_string(object).unwrap();
+    let client = Client::new();
+    let address = format!("http://{}/public/v1/action/{}", connect_string, action_name);
+    check_action_response(action_name, client.post(&address).body(&data).header(ContentType::json()).send())
 }
 
-pub fn add_route(connect_string: &str, route_from: &str, route_to: &str) {
-    let (ip, mask) = split_address_into_ip_and_mask(&route_from);
-    let nexthop = NextHopInfo { NextHopIp: route_to.to_string() };
-    let ipv4route = IPv4Route {
-        DestinationNw: ip,
-        NetworkMask: mask,
-        Protocol: "STATIC".to_string(),
-        NextHop: vec![nexthop],
-    };
+fn check_action_response(action_name: &str, result: hyper::Result<hyper::client::Response>) -> Result<(), String> {
+    let mut response = result.map_err(|err| format!("{} failed: {}", action_name, err))?;
 
-    let data = json::encode(&ipv4route).unwrap();
-    let client = Client::new();
-    let address = format!("http://{}/public/v1/config/IPv4Route", connect_string);
-    log_request_error!(client.post(&address).body(&data).header(ContentType::json()).send());
+    match response.status.to_u16() >= 400 {
+        true => {
+            let mut body = String::new();
+            response.read_to_string(&mut body).unwrap_or(0);
+            Err(format!("{} failed with {}: {}", action_name, response.status, body.trim()))
+        }
+        false => Ok(()),
+    }
 }
 
-pub fn delete_route(connect_string: &str, route_from: &str) {
-    let (ip, mask) = split_address_into_ip_and_mask(&route_from);
-    let ipv4route = IPv4Route {
-        DestinationNw: ip,
-        NetworkMask: mask,
-        Protocol: "STATIC".to_string(),
-        NextHop: vec![],
-    };
-
-    let data = json::encode(&ipv4route).unwrap();
-    let client = Client::new();
-    let address = format!("http://{}/public/v1/config/IPv4Route", connect_string);
-    log_request_error!(client.delete(&address).body(&data).header(ContentType::json()).send());
+// looks up the ObjectId of the object with the same key fields and deletes it through /config/<Obj>/<ObjectId>
+fn delete_config_object_by_id<T: ConfigObject>(connect_string: &str, object: &T) {
+    match get_object_id(connect_string, object) {
+        Ok(Some(object_id)) => {
+            if let Err(err) = delete_by_id::<T>(connect_string, &object_id) {
+                println!("error {}", err);
+            }
+        }
+        Ok(None) => println!("error unknown {}", T::object_name()),
+        Err(err) => println!("error {}", err),
+    }
 }
 
+// per lane readings like RX1Power .. RX4Power, only lanes reported by the module are returned
+fn read_lanes(lanes: &[Option<f64>]) -> Vec<f64> {
+    lanes.iter().map_while(|lane| *lane).collect()
+}
 
 fn read_config_file(config_file: &str) -> Yaml {
     let mut file = match File::open(config_file) {
         Ok(file) => file,
-        Err(err) => panic!(err.to_string()),
+        Err(err) => panic!("{}", err.to_string()),
     };
 
     let mut content = String::new();
@@ -283,7 +2502,8 @@ fn read_ports(config: &Yaml) -> Vec<Port> {
                     false => {
                         let definition = Port {
                             IntfRef: port["name"].as_str().unwrap().to_string(),
-                            BreakOutMode: port["mode"].as_str().unwrap().to_string(),
+                            BreakOutMode: Some(port["mode"].as_str().unwrap().to_string()),
+                            ..Default::default()
                         };
                         result.push(definition);
                     }
@@ -295,7 +2515,8 @@ fn read_ports(config: &Yaml) -> Vec<Port> {
     result.clone()
 }
 
-fn read_sub_ports(config: &Yaml) -> Vec<SubPort> {
+// speed and admin state of ports, separate from the breakout modes they depend on
+fn read_sub_ports(config: &Yaml) -> Vec<Port> {
     let mut result = Vec::new();
 
     match config["ports"].is_badvalue() {
@@ -306,10 +2527,11 @@ fn read_sub_ports(config: &Yaml) -> Vec<SubPort> {
                 match port["speed"].is_badvalue() {
                     true => {}
                     false => {
-                        let definition = SubPort {
+                        let definition = Port {
                             IntfRef: port["name"].as_str().unwrap().to_string(),
-                            Speed: port["speed"].as_i64().unwrap() as i32,
-                            AdminState: "UP".to_string(),
+                            Speed: Some(port["speed"].as_i64().unwrap() as i32),
+                            AdminState: Some("UP".to_string()),
+                            ..Default::default()
                         };
                         result.push(definition);
                     }
@@ -329,8 +2551,7 @@ fn read_vlans(config: &Yaml) -> Vec<Vlan> {
         false => {
             let vlans = config["vlans"].as_vec().unwrap();
             for vlan in vlans {
-                let mut inf_list = Vec::new();
-                inf_list.push(vlan["ports"].as_str().unwrap().to_string());
+                let inf_list = vec![vlan["ports"].as_str().unwrap().to_string()];
                 let definition = Vlan {
                     VlanId: vlan["id"].as_i64().unwrap() as i32,
                     UntagIntfList: inf_list,
@@ -343,7 +2564,135 @@ fn read_vlans(config: &Yaml) -> Vec<Vlan> {
     result.clone()
 }
 
-fn read_ipv4intf(config: &Yaml) -> Vec<IPv4Intf> {
+// an invalid stp section is skipped as a whole
+fn read_stp(config: &Yaml) -> (Vec<StpBridgeInstance>, Vec<StpPort>) {
+    match config["stp"].is_badvalue() {
+        true => (vec![], vec![]),
+        false => {
+            match read_stp_config(&config["stp"]) {
+                Ok(stp) => stp,
+                Err(err) => {
+                    println!("error {}, skipping stp", err);
+                    (vec![], vec![])
+                }
+            }
+        }
+    }
+}
+
+fn read_stp_config(stp: &Yaml) -> Result<(Vec<StpBridgeInstance>, Vec<StpPort>), String> {
+    let mode = match stp["mode"] {
+        Yaml::BadValue => "rstp",
+        ref mode => mode.as_str().ok_or("invalid stp mode")?,
+    };
+    // stp and rstp run a single instance on the default bridge vlan, pvst one instance per vlan
+    let (force_version, vlans) = match mode {
+        "stp" => (1, vec![4095]),
+        "rstp" => (2, vec![4095]),
+        "pvst" => (2, read_stp_vlans(stp)?),
+        other => return Err(format!("unknown stp mode {}", other)),
+    };
+    let priority = match stp["priority"] {
+        Yaml::BadValue => 32768,
+        ref priority => priority.as_i64().ok_or("invalid stp priority")? as i32,
+    };
+
+    let mut port_names = vec![];
+    if !stp["ports"].is_badvalue() {
+        for port in stp["ports"].as_vec().ok_or("invalid stp ports")? {
+            port_names.push((port["name"].as_str().ok_or("missing stp port name")?, port));
+        }
+    }
+
+    let mut bridges = Vec::new();
+    let mut ports = Vec::new();
+    for vlan in vlans {
+        bridges.push(StpBridgeInstance {
+            Vlan: vlan,
+            Priority: priority,
+            ForceVersion: force_version,
+        });
+        for (port_name, port) in &port_names {
+            ports.push(StpPort {
+                Vlan: vlan,
+                IntfRef: port_name.to_string(),
+                Enable: true,
+                AdminEdgePort: port["edge"].as_bool().unwrap_or(false),
+                BpduGuard: port["bpdu_guard"].as_bool().unwrap_or(false),
+            });
+        }
+    }
+
+    Ok((bridges, ports))
+}
+
+// the vlans pvst runs one instance for each
+fn read_stp_vlans(stp: &Yaml) -> Result<Vec<i32>, String> {
+    let mut result = Vec::new();
+
+    for vlan in stp["vlans"].as_vec().ok_or("pvst needs a list of vlans")? {
+        match vlan.as_i64() {
+            Some(vlan) if (1..=4094).contains(&vlan) => result.push(vlan as i32),
+            _ => return Err(format!("invalid stp vlan {:?}", vlan)),
+        }
+    }
+
+    Ok(result)
+}
+
+fn read_logical_intf(config: &Yaml) -> Vec<LogicalIntf> {
+    let mut result = Vec::new();
+
+    match config["interfaces"].is_badvalue() {
+        true => {}
+        false => {
+            let interfaces = config["interfaces"].as_vec().unwrap();
+            for interface in interfaces {
+                match interface["loopback"].is_badvalue() {
+                    true => {}
+                    false => {
+                        let definition = LogicalIntf {
+                            Name: interface["loopback"].as_str().unwrap().to_string(),
+                            Type: "Loopback".to_string(),
+                        };
+                        result.push(definition);
+                    }
+                }
+            }
+        }
+    }
+
+    result.clone()
+}
+
+// an interface entry names a loopback, a port or sub-interface, or refers to the vlan interface
+fn read_intf_ref(interface: &Yaml) -> String {
+    if !interface["loopback"].is_badvalue() {
+        interface["loopback"].as_str().unwrap().to_string()
+    } else if !interface["port"].is_badvalue() {
+        interface["port"].as_str().unwrap().to_string()
+    } else {
+        format!("vlan{}", interface["vlan_id"].as_i64().unwrap())
+    }
+}
+
+// "fpPort3.200" is vlan 200 on port fpPort3
+fn split_sub_intf_ref(intf_ref: &str) -> Result<(String, Option<i32>), String> {
+    let mut parts = intf_ref.splitn(2, '.');
+    let port_name = parts.next().unwrap().to_string();
+    let vlan_id = match parts.next() {
+        Some(vlan_id) => {
+            match vlan_id.parse::<i32>() {
+                Ok(vlan_id) if (1..=4094).contains(&vlan_id) => Some(vlan_id),
+                _ => return Err(format!("invalid vlan in sub-interface {}", intf_ref)),
+            }
+        }
+        None => None,
+    };
+    Ok((port_name, vlan_id))
+}
+
+fn read_interface_ports(config: &Yaml) -> Vec<String> {
     let mut result = Vec::new();
 
     match config["interfaces"].is_badvalue() {
@@ -351,27 +2700,593 @@ fn read_ipv4intf(config: &Yaml) -> Vec<IPv4Intf> {
         false => {
             let interfaces = config["interfaces"].as_vec().unwrap();
             for interface in interfaces {
-                let definition = IPv4Intf {
-                    IntfRef: format!("vlan{}", interface["vlan_id"].as_i64().unwrap()),
-                    IpAddr: interface["addr"].as_str().unwrap().to_string(),
+                if let Some(port) = interface["port"].as_str() {
+                    let port_name = match split_sub_intf_ref(port) {
+                        Ok((port_name, _)) => port_name,
+                        Err(_) => continue,
+                    };
+                    if !result.contains(&port_name) {
+                        result.push(port_name);
+                    }
+                }
+            }
+        }
+    }
+
+    result.clone()
+}
+
+// an entry of the interfaces section, sub-interfaces like "fpPort3.200" are split into port and vlan
+struct Interface<'a> {
+    config: &'a Yaml,
+    intf_ref: String,
+    port_name: String,
+    vlan_id: Option<i32>,
+}
+
+// entries with an invalid sub-interface are reported and skipped
+fn read_interfaces(config: &Yaml) -> Vec<Interface<'_>> {
+    let mut result = Vec::new();
+
+    for interface in config["interfaces"].as_vec().map(|interfaces| interfaces.as_slice()).unwrap_or(&[]) {
+        let intf_ref = read_intf_ref(interface);
+        match split_sub_intf_ref(&intf_ref) {
+            Ok((port_name, vlan_id)) => {
+                result.push(Interface {
+                    config: interface,
+                    intf_ref,
+                    port_name,
+                    vlan_id,
+                })
+            }
+            Err(err) => println!("error {}, skipping it", err),
+        }
+    }
+
+    result
+}
+
+fn read_ipv4intf(interfaces: &[Interface]) -> Vec<IPv4Intf> {
+    interfaces.iter()
+        .filter(|interface| interface.vlan_id.is_none())
+        .filter_map(|interface| {
+            interface.config["addr"].as_str().map(|addr| {
+                IPv4Intf {
+                    IntfRef: interface.port_name.clone(),
+                    IpAddr: addr.to_string(),
+                }
+            })
+        })
+        .collect()
+}
+
+fn read_sub_ipv4intf(interfaces: &[Interface]) -> Vec<SubIPv4Intf> {
+    interfaces.iter()
+        .filter_map(|interface| {
+            let vlan_id = interface.vlan_id?;
+            interface.config["addr"].as_str().map(|addr| {
+                SubIPv4Intf {
+                    IntfRef: interface.port_name.clone(),
+                    IpAddr: addr.to_string(),
+                    VlanId: vlan_id,
+                    Enable: true,
+                }
+            })
+        })
+        .collect()
+}
+
+fn read_ipv6intf(interfaces: &[Interface]) -> Vec<IPv6Intf> {
+    interfaces.iter()
+        .filter(|interface| interface.vlan_id.is_none())
+        .filter_map(|interface| {
+            interface.config["ipv6_addr"].as_str().map(|ipv6_addr| {
+                IPv6Intf {
+                    IntfRef: interface.port_name.clone(),
+                    IpAddr: ipv6_addr.to_string(),
+                    LinkIp: interface.config["ipv6_link_local"].as_bool().unwrap_or(true),
+                }
+            })
+        })
+        .collect()
+}
+
+fn read_sub_ipv6intf(interfaces: &[Interface]) -> Vec<SubIPv6Intf> {
+    interfaces.iter()
+        .filter_map(|interface| {
+            let vlan_id = interface.vlan_id?;
+            interface.config["ipv6_addr"].as_str().map(|ipv6_addr| {
+                SubIPv6Intf {
+                    IntfRef: interface.port_name.clone(),
+                    IpAddr: ipv6_addr.to_string(),
+                    VlanId: vlan_id,
+                    LinkIp: interface.config["ipv6_link_local"].as_bool().unwrap_or(true),
+                    Enable: true,
+                }
+            })
+        })
+        .collect()
+}
+
+fn read_vrrp_intf(interfaces: &[Interface]) -> Vec<VrrpIntf> {
+    let mut result = Vec::new();
+
+    for interface in interfaces {
+        let vrrp = &interface.config["vrrp"];
+        match vrrp.is_badvalue() {
+            true => {}
+            false => {
+                let definition = VrrpIntf {
+                    IntfRef: interface.intf_ref.clone(),
+                    VRID: vrrp["vrid"].as_i64().unwrap() as i32,
+                    VirtualIPv4Addr: vrrp["virtual_ip"].as_str().unwrap().to_string(),
+                    Priority: vrrp["priority"].as_i64().unwrap_or(100) as i32,
+                    PreemptMode: vrrp["preempt"].as_bool().unwrap_or(true),
+                    AdvertisementInterval: vrrp["advertisement_interval"].as_i64().unwrap_or(1) as i32,
                 };
                 result.push(definition);
             }
         }
     }
 
-    result.clone()
+    result
+}
+
+fn read_dhcp_relay_intf(interfaces: &[Interface]) -> Vec<DhcpRelayIntf> {
+    let mut result = Vec::new();
+
+    for interface in interfaces {
+        match interface.config["dhcp_relay"].is_badvalue() {
+            true => {}
+            false => {
+                let mut servers = Vec::new();
+                for server in interface.config["dhcp_relay"].as_vec().unwrap() {
+                    servers.push(server.as_str().unwrap().to_string());
+                }
+                let definition = DhcpRelayIntf {
+                    IntfRef: interface.intf_ref.clone(),
+                    Enable: true,
+                    ServerIp: servers,
+                };
+                result.push(definition);
+            }
+        }
+    }
+
+    result
+}
+
+// an acl with an invalid rule is skipped as a whole, rather than applied without that rule
+fn read_acls(config: &Yaml) -> Vec<(Acl, Vec<AclRule>)> {
+    let mut result = Vec::new();
+
+    match config["acls"].is_badvalue() {
+        true => {}
+        false => {
+            let acls = config["acls"].as_vec().unwrap();
+            for acl in acls {
+                match read_acl(acl) {
+                    Ok(definition) => result.push(definition),
+                    Err(err) => println!("error {}, skipping acl {}", err, acl["name"].as_str().unwrap_or("")),
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn read_acl(acl: &Yaml) -> Result<(Acl, Vec<AclRule>), String> {
+    let acl_name = acl["name"].as_str().ok_or("missing acl name")?;
+
+    let mut rules = Vec::new();
+    for rule in acl["rules"].as_vec().ok_or("missing acl rules")? {
+        rules.push(read_acl_rule(rule)?);
+    }
+
+    // "fpPort1, fpPort2" or a list of ports
+    let ports = match acl["ports"] {
+        Yaml::String(ref ports) => ports.split(',').map(|port| port.trim().to_string()).collect(),
+        Yaml::Array(ref ports) => {
+            ports.iter()
+                .map(|port| port.as_str().map(|port| port.to_string()).ok_or("invalid port in acl ports"))
+                .collect::<Result<Vec<String>, &str>>()?
+        }
+        _ => return Err("missing acl ports".to_string()),
+    };
+
+    let definition = Acl {
+        AclName: acl_name.to_string(),
+        AclType: acl["type"].as_str().unwrap_or("IPv4").to_string(),
+        IntfList: ports,
+        RuleNameList: rules.iter().map(|rule| rule.RuleName.clone()).collect(),
+        Direction: acl["direction"].as_str().unwrap_or("in").to_uppercase(),
+    };
+
+    Ok((definition, rules))
+}
+
+fn read_acl_rule(rule: &Yaml) -> Result<AclRule, String> {
+    let mut definition = empty_acl_rule();
+    definition.RuleName = rule["name"].as_str().ok_or("missing acl rule name")?.to_string();
+    definition.SourceMac = rule["src_mac"].as_str().unwrap_or("").to_string();
+    definition.DestMac = rule["dst_mac"].as_str().unwrap_or("").to_string();
+    if let Some(src_ip) = rule["src_ip"].as_str() {
+        let (ip, mask) = split_address_into_ip_and_mask(src_ip)
+            .map_err(|err| format!("{} in acl rule {}", err, definition.RuleName))?;
+        definition.SourceIp = ip;
+        definition.SourceMask = mask;
+    }
+    if let Some(dst_ip) = rule["dst_ip"].as_str() {
+        let (ip, mask) = split_address_into_ip_and_mask(dst_ip)
+            .map_err(|err| format!("{} in acl rule {}", err, definition.RuleName))?;
+        definition.DestIp = ip;
+        definition.DestMask = mask;
+    }
+    definition.Proto = rule["proto"].as_str().unwrap_or("").to_string();
+    definition.L4SrcPort = rule["src_port"].as_i64().unwrap_or(0) as i32;
+    definition.L4DstPort = rule["dst_port"].as_i64().unwrap_or(0) as i32;
+    definition.Action = rule["action"].as_str().unwrap_or("deny").to_uppercase();
+
+    Ok(definition)
+}
+
+// "10.0.0.0/8 le 24" or "10.0.0.0/8 ge 16 le 24", a plain prefix only matches exactly
+fn parse_policy_prefix(words: &[&str]) -> Result<PolicyPrefix, String> {
+    let (ip, prefix_len) = split_address_into_ip_and_prefix_len(words[0])?;
+    let mut min_len = None;
+    let mut max_len = None;
+
+    for pair in words[1..].chunks(2) {
+        if pair.len() != 2 {
+            return Err(format!("missing length after '{}'", pair[0]));
+        }
+        let len = pair[1].parse::<u32>().map_err(|_| format!("invalid length '{}'", pair[1]))?;
+        match pair[0] {
+            "le" => max_len = Some(len),
+            "ge" => min_len = Some(len),
+            other => return Err(format!("unexpected '{}'", other)),
+        }
+    }
+
+    let mask_length_range = match (min_len, max_len) {
+        (None, None) => "exact".to_string(),
+        (min_len, max_len) => {
+            let (min_len, max_len) = (min_len.unwrap_or(prefix_len), max_len.unwrap_or(32));
+            if prefix_len > min_len || min_len > max_len || max_len > 32 {
+                return Err(format!("lengths have to satisfy {} <= ge <= le <= 32, got ge {} le {}",
+                                   prefix_len,
+                                   min_len,
+                                   max_len));
+            }
+            format!("{}..{}", min_len, max_len)
+        }
+    };
+
+    Ok(PolicyPrefix {
+        Prefix: format!("{}/{}", ip, prefix_len),
+        MaskLengthRange: mask_length_range,
+    })
+}
+
+// an address without prefix length is a host address, like /32
+fn split_address_into_ip_and_prefix_len(address: &str) -> Result<(String, u32), String> {
+    let mut parts = address.splitn(2, '/');
+    let ip = parts.next().unwrap().to_string();
+    let prefix_len = match parts.next() {
+        Some(prefix_len) => {
+            match prefix_len.parse::<u32>() {
+                Ok(prefix_len) if prefix_len <= 32 => prefix_len,
+                _ => return Err(format!("invalid prefix length in {}", address)),
+            }
+        }
+        None => 32,
+    };
+    Ok((ip, prefix_len))
+}
+
+fn split_address_into_ip_and_mask(address: &str) -> Result<(String, String), String> {
+    let (ip, prefix_len) = split_address_into_ip_and_prefix_len(address)?;
+
+    let bits = match prefix_len {
+        0 => 0,
+        _ => !0u32 << (32 - prefix_len),
+    };
+    let mask = format!("{}.{}.{}.{}", bits >> 24, (bits >> 16) & 0xff, (bits >> 8) & 0xff, bits & 0xff);
+    Ok((ip, mask))
 }
 
-fn split_address_into_ip_and_mask(address: &str) -> (String, String) {
-    let len = address.len();
-    let mut ip = address.to_string();
-    let mut mask = "255.255.255.255".to_string();
-    if address.ends_with("/32") {
-        ip.truncate(len - 3);
-    } else if address.ends_with("/24") {
-        ip.truncate(len - 3);
-        mask = "255.255.255.0".to_string()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_address_into_ip_and_mask_by_prefix_length() {
+        let mask = |address| split_address_into_ip_and_mask(address).unwrap().1;
+
+        assert_eq!(mask("0.0.0.0/0"), "0.0.0.0");
+        assert_eq!(mask("10.1.0.0/16"), "255.255.0.0");
+        assert_eq!(mask("10.1.2.0/24"), "255.255.255.0");
+        assert_eq!(mask("10.1.2.3/32"), "255.255.255.255");
+        assert_eq!(split_address_into_ip_and_mask("10.1.2.3").unwrap(),
+                   ("10.1.2.3".to_string(), "255.255.255.255".to_string()));
+    }
+
+    #[test]
+    fn split_address_into_ip_and_mask_rejects_invalid_prefix_length() {
+        assert!(split_address_into_ip_and_mask("10.0.0.0/33").is_err());
+        assert!(split_address_into_ip_and_mask("10.0.0.0/x").is_err());
+        assert!(split_address_into_ip_and_mask("10.0.0.0/").is_err());
+        assert!(split_address_into_ip_and_mask("10.0.0.0/-1").is_err());
+    }
+
+    #[test]
+    fn split_sub_intf_ref_into_port_and_vlan() {
+        assert_eq!(split_sub_intf_ref("fpPort3").unwrap(), ("fpPort3".to_string(), None));
+        assert_eq!(split_sub_intf_ref("fpPort3.200").unwrap(), ("fpPort3".to_string(), Some(200)));
+        assert!(split_sub_intf_ref("fpPort3.abc").is_err());
+        assert!(split_sub_intf_ref("fpPort3.0").is_err());
+        assert!(split_sub_intf_ref("fpPort3.4095").is_err());
+    }
+
+    #[test]
+    fn read_interface_requests_skips_unknown_ports_and_invalid_sub_interfaces() {
+        let config = YamlLoader::load_from_str("
+interfaces:
+    - port: fpPort1
+      addr: 192.168.1.2/30
+    - port: fpPort9
+      addr: 192.168.9.2/30
+      ipv6_addr: 2001:db8:9::2/64
+      vrrp:
+          vrid: 1
+          virtual_ip: 192.168.9.1
+      dhcp_relay:
+          - 10.0.0.5
+    - port: fpPort1.abc
+      addr: 192.168.2.2/30
+      dhcp_relay:
+          - 10.0.0.5
+").unwrap();
+
+        let requests = read_interface_requests(&config[0], &["fpPort1".to_string()]);
+        let objects: Vec<(&str, &str)> = requests.iter().map(|request| (request.object_name, request.data.as_str())).collect();
+
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].0, "IPv4Intf");
+        assert!(objects[0].1.contains("192.168.1.2/30"));
+    }
+
+    #[test]
+    fn read_interface_requests_with_ipv6_on_sub_interface() {
+        let config = YamlLoader::load_from_str("
+interfaces:
+    - port: fpPort1.200
+      addr: 192.168.200.2/30
+      ipv6_addr: 2001:db8:200::2/64
+").unwrap();
+
+        let requests = read_interface_requests(&config[0], &["fpPort1".to_string()]);
+        let objects: Vec<&str> = requests.iter().map(|request| request.object_name).collect();
+        assert_eq!(objects, ["SubIPv4Intf", "SubIPv6Intf"]);
+
+        let sub_ipv6_intf: SubIPv6Intf = serde_json::from_str(&requests[1].data).unwrap();
+        assert_eq!(sub_ipv6_intf.IntfRef, "fpPort1");
+        assert_eq!(sub_ipv6_intf.VlanId, 200);
+        assert_eq!(sub_ipv6_intf.IpAddr, "2001:db8:200::2/64");
+    }
+
+    #[test]
+    fn read_port_requests_with_rstp() {
+        let config = YamlLoader::load_from_str("
+stp:
+    ports:
+        - name: fpPort1
+          edge: true
+").unwrap();
+
+        let requests = read_port_requests(&config[0]);
+        let objects: Vec<&str> = requests.iter().map(|request| request.object_name).collect();
+        assert_eq!(objects, ["StpBridgeInstance", "StpPort"]);
+
+        let bridge: StpBridgeInstance = serde_json::from_str(&requests[0].data).unwrap();
+        assert_eq!((bridge.Vlan, bridge.Priority, bridge.ForceVersion), (4095, 32768, 2));
+        let port: StpPort = serde_json::from_str(&requests[1].data).unwrap();
+        assert_eq!((port.Vlan, port.IntfRef.as_str(), port.AdminEdgePort, port.BpduGuard), (4095, "fpPort1", true, false));
+    }
+
+    #[test]
+    fn read_stp_config_expands_pvst_per_vlan() {
+        let config = YamlLoader::load_from_str("
+mode: pvst
+priority: 4096
+vlans: [100, 200]
+ports:
+    - name: fpPort1
+    - name: fpPort2
+").unwrap();
+
+        let (bridges, ports) = read_stp_config(&config[0]).unwrap();
+        let bridges: Vec<(i32, i32, i32)> = bridges.iter().map(|bridge| (bridge.Vlan, bridge.Priority, bridge.ForceVersion)).collect();
+        assert_eq!(bridges, [(100, 4096, 2), (200, 4096, 2)]);
+        let ports: Vec<(i32, &str)> = ports.iter().map(|port| (port.Vlan, port.IntfRef.as_str())).collect();
+        assert_eq!(ports, [(100, "fpPort1"), (100, "fpPort2"), (200, "fpPort1"), (200, "fpPort2")]);
+
+        let config = YamlLoader::load_from_str("mode: stp").unwrap();
+        let (bridges, ports) = read_stp_config(&config[0]).unwrap();
+        assert_eq!((bridges[0].Vlan, bridges[0].ForceVersion), (4095, 1));
+        assert!(ports.is_empty());
+    }
+
+    #[test]
+    fn read_stp_config_rejects_invalid_config() {
+        let rejects = |stp| read_stp_config(&YamlLoader::load_from_str(stp).unwrap()[0]).is_err();
+
+        assert!(rejects("mode: pvst"));
+        assert!(rejects("mode: pvst\nvlans: [100, abc]"));
+        assert!(rejects("mode: pvst\nvlans: [5000]"));
+        assert!(rejects("mode: mstp"));
+        assert!(rejects("priority: high"));
+        assert!(rejects("ports:\n    - edge: true"));
+
+        let config = YamlLoader::load_from_str("stp:\n    mode: mstp").unwrap();
+        assert!(read_port_requests(&config[0]).is_empty());
+    }
+
+    #[test]
+    fn read_interface_requests_with_acls() {
+        let config = YamlLoader::load_from_str("
+acls:
+    - name: block-mgmt
+      ports: fpPort1, fpPort2
+      rules:
+          - name: deny-mgmt
+            dst_ip: 10.250.0.0/16
+    - name: block-lab
+      ports:
+          - fpPort1
+          - fpPort2s1
+      rules:
+          - name: deny-lab
+            src_ip: 10.1.0.0/16
+            action: deny
+          - name: deny-bad
+            dst_ip: 10.2.0.0/33
+    - name: block-any
+      ports:
+          - 1
+      rules:
+          - name: deny-any
+").unwrap();
+
+        let requests = read_interface_requests(&config[0], &[]);
+        let objects: Vec<&str> = requests.iter().map(|request| request.object_name).collect();
+        assert_eq!(objects, ["AclRule", "Acl"]);
+
+        let acl: Acl = serde_json::from_str(&requests[1].data).unwrap();
+        assert_eq!(acl.AclName, "block-mgmt");
+        assert_eq!(acl.IntfList, ["fpPort1", "fpPort2"]);
+        assert_eq!(acl.RuleNameList, ["deny-mgmt"]);
+    }
+
+    #[test]
+    fn read_acl_with_port_list() {
+        let config = YamlLoader::load_from_str("
+name: block-lab
+direction: out
+ports:
+    - fpPort1
+    - fpPort2s1
+rules:
+    - name: deny-lab
+      src_ip: 10.1.0.0/16
+    - name: permit-web
+      proto: tcp
+      dst_port: 80
+      action: permit
+").unwrap();
+
+        let (acl, rules) = read_acl(&config[0]).unwrap();
+        assert_eq!(acl.IntfList, ["fpPort1", "fpPort2s1"]);
+        assert_eq!(acl.RuleNameList, ["deny-lab", "permit-web"]);
+        assert_eq!(acl.Direction, "OUT");
+        assert_eq!((rules[0].SourceIp.as_str(), rules[0].SourceMask.as_str()), ("10.1.0.0", "255.255.0.0"));
+        assert_eq!(rules[1].Action, "PERMIT");
+    }
+
+    #[test]
+    fn build_policy_from_rule() {
+        let policy = build_policy("import", 10, &["permit 10.0.0.0/8 le 24, set local-pref 200"]).unwrap();
+
+        assert_eq!(policy.prefix_sets.len(), 1);
+        assert_eq!(policy.prefix_sets[0].Name, "import_1_prefixes");
+        assert_eq!(policy.prefix_sets[0].PrefixList[0].Prefix, "10.0.0.0/8");
+        assert_eq!(policy.prefix_sets[0].PrefixList[0].MaskLengthRange, "8..24");
+
+        assert_eq!(policy.conditions.len(), 1);
+        assert_eq!(policy.conditions[0].ConditionType, "MatchDstIpPrefix");
+        assert_eq!(policy.conditions[0].PrefixSet, "import_1_prefixes");
+
+        assert_eq!(policy.statements.len(), 1);
+        assert_eq!(policy.statements[0].Action, "permit");
+        assert_eq!(policy.statements[0].Conditions, ["import_1_match"]);
+        assert_eq!(policy.statements[0].SetActions[0].Attr, "LocalPref");
+        assert_eq!(policy.statements[0].SetActions[0].Value, "200");
+
+        assert_eq!(policy.definition.Name, "import");
+        assert_eq!(policy.definition.Priority, 10);
+        assert_eq!(policy.definition.StatementList.len(), 1);
+    }
+
+    #[test]
+    fn build_policy_mask_length_ranges() {
+        let range = |rule| build_policy("p", 10, &[rule]).unwrap().prefix_sets[0].PrefixList[0].MaskLengthRange.clone();
+
+        assert_eq!(range("permit 10.0.0.0/8"), "exact");
+        assert_eq!(range("permit 10.0.0.0/8 ge 16"), "16..32");
+        assert_eq!(range("permit 10.0.0.0/8 ge 16 le 24"), "16..24");
+        assert_eq!(range("permit 10.0.0.0/24 le 24"), "24..24");
+    }
+
+    #[test]
+    fn build_policy_rejects_invalid_rules() {
+        let rejects = |rule| build_policy("p", 10, &[rule]).is_err();
+
+        assert!(rejects("permit 10.0.0.0/x"));
+        assert!(rejects("permit 10.0.0.0/33"));
+        assert!(rejects("permit 10.0.0.0/33 le 24"));
+        assert!(rejects("permit 10.0.0.0/8 ge 30 le 24"));
+        assert!(rejects("permit 10.0.0.0/16 le 8"));
+        assert!(rejects("permit 10.0.0.0/16 ge 8"));
+        assert!(rejects("permit 10.0.0.0/8 le 33"));
+        assert!(rejects("permit 10.0.0.0/8 le"));
+        assert!(rejects("permit 10.0.0.0/8 eq 24"));
+        assert!(rejects("accept 10.0.0.0/8"));
+        assert!(rejects("permit 10.0.0.0/8, set weight 10"));
+        assert!(rejects("permit 10.0.0.0/8, local-pref 200"));
+    }
+
+    #[test]
+    fn static_route_encoding() {
+        let route = static_route("10.1.0.0/24", Some("192.168.0.1")).unwrap();
+
+        assert_eq!(serde_json::to_value(&route).unwrap(),
+                   serde_json::json!({
+                       "DestinationNw": "10.1.0.0",
+                       "NetworkMask": "255.255.255.0",
+                       "Protocol": "STATIC",
+                       "NextHop": [{"NextHopIp": "192.168.0.1"}]
+                   }));
+    }
+
+    #[test]
+    fn port_encodes_only_the_members_set() {
+        let port = Port {
+            IntfRef: "fpPort1".to_string(),
+            BreakOutMode: Some("4x10".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(serde_json::to_value(&port).unwrap(),
+                   serde_json::json!({"IntfRef": "fpPort1", "BreakOutMode": "4x10"}));
+    }
+
+    #[test]
+    fn state_structs_default_nulls_and_reject_mismatched_types() {
+        let entry: MacTableEntryState =
+            serde_json::from_value(serde_json::json!({"MacAddr": "00:11:22:33:44:55", "VlanId": null})).unwrap();
+        assert_eq!(entry.VlanId, 0);
+        assert_eq!(entry.Type, "");
+
+        assert!(serde_json::from_value::<MacTableEntryState>(serde_json::json!({"VlanId": 10})).is_err());
+        assert!(serde_json::from_value::<MacTableEntryState>(serde_json::json!({"MacAddr": "00:11:22:33:44:55",
+                                                                                "VlanId": "10"}))
+            .is_err());
+    }
+
+    #[test]
+    fn read_lanes_stops_at_the_first_missing_lane() {
+        assert_eq!(read_lanes(&[Some(1.0), Some(2.0), None, Some(4.0)]), vec![1.0, 2.0]);
+        assert!(read_lanes(&[None, None, None, None]).is_empty());
     }
-    (ip.clone(), mask.clone())
 }
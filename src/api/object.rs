@@ -0,0 +1,281 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2015 AT&T
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+// Generic access to FlexSwitch config objects under /public/v1/config.
+//
+// Typed objects implement ConfigObject, any other FlexSwitch object can be
+// used untyped through the *_json functions and a JsonObject.
+
+use hyper::Client;
+use hyper::header::ContentType;
+use hyper::method::Method;
+use hyper::status::StatusCode;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{self, Value};
+use std::io::Read;
+
+use super::{Acl, AclRule, BfdGlobal, BfdSession, BfdSessionParam, DhcpRelayGlobal, DhcpRelayIntf, IPv4Intf,
+            IPv4Route, IPv6Intf, LLDPGlobal, LLDPIntf, LogicalIntf, PolicyCondition, PolicyDefinition,
+            PolicyPrefixSet, PolicyStmt, Port, RouteDistance, StpBridgeInstance, StpPort, SubIPv4Intf, SubIPv6Intf,
+            SystemParam, Vlan, VrrpIntf};
+use super::decode_body;
+use super::envelope::{CreateResponse, ObjectEnvelope, ObjectList};
+
+pub type JsonObject = serde_json::Map<String, Value>;
+
+pub trait ConfigObject: Serialize + DeserializeOwned {
+    // name of the object in the config url, like "Vlan" for /public/v1/config/Vlan
+    fn object_name() -> &'static str;
+
+    // fields identifying one object, sent on get and delete
+    fn key_fields() -> &'static [&'static str];
+}
+
+macro_rules! config_objects {
+    ($($object:ty, $name:tt, [$($key:expr),*];)*) => (
+        $(
+            impl ConfigObject for $object {
+                fn object_name() -> &'static str {
+                    $name
+                }
+
+                fn key_fields() -> &'static [&'static str] {
+                    &[$($key),*]
+                }
+            }
+        )*
+
+        // key fields of the objects above by their FlexSwitch name, for the mock switch
+        #[cfg(feature = "mock-server")]
+        pub(crate) fn key_fields_by_name(object_name: &str) -> Option<&'static [&'static str]> {
+            match object_name {
+                $($name => Some(&[$($key),*]),)*
+                _ => None,
+            }
+        }
+    )
+}
+
+config_objects! {
+    Port, "Port", ["IntfRef"];
+    Vlan, "Vlan", ["VlanId"];
+    StpBridgeInstance, "StpBridgeInstance", ["Vlan"];
+    StpPort, "StpPort", ["IntfRef", "Vlan"];
+    LogicalIntf, "LogicalIntf", ["Name"];
+    IPv4Intf, "IPv4Intf", ["IntfRef"];
+    SubIPv4Intf, "SubIPv4Intf", ["IntfRef", "IpAddr"];
+    IPv6Intf, "IPv6Intf", ["IntfRef"];
+    SubIPv6Intf, "SubIPv6Intf", ["IntfRef", "IpAddr"];
+    VrrpIntf, "VrrpIntf", ["IntfRef", "VRID"];
+    DhcpRelayGlobal, "DhcpRelayGlobal", ["Vrf"];
+    DhcpRelayIntf, "DhcpRelayIntf", ["IntfRef"];
+    AclRule, "AclRule", ["RuleName"];
+    Acl, "Acl", ["AclName"];
+    IPv4Route, "IPv4Route", ["DestinationNw", "NetworkMask"];
+    RouteDistance, "RouteDistance", ["Protocol"];
+    SystemParam, "SystemParam", ["Vrf"];
+    LLDPGlobal, "LLDPGlobal", ["Vrf"];
+    LLDPIntf, "LLDPIntf", ["IntfRef"];
+    BfdGlobal, "BfdGlobal", ["Vrf"];
+    BfdSessionParam, "BfdSessionParam", ["Name"];
+    BfdSession, "BfdSession", ["IpAddr"];
+    PolicyPrefixSet, "PolicyPrefixSet", ["Name"];
+    PolicyCondition, "PolicyCondition", ["Name"];
+    PolicyStmt, "PolicyStmt", ["Name"];
+    PolicyDefinition, "PolicyDefinition", ["Name"];
+}
+
+// returns the ObjectId the switch assigned to the new object
+pub fn create<T: ConfigObject>(connect_string: &str, object: &T) -> Result<String, String> {
+    create_json(connect_string, T::object_name(), &encode_object(object)?)
+}
+
+// looks up the object with the same key fields as the given one
+pub fn get<T: ConfigObject>(connect_string: &str, object: &T) -> Result<Option<T>, String> {
+    get_by_key(connect_string, &encode_key(object)?)
+}
+
+pub fn get_by_key<T: ConfigObject>(connect_string: &str, key: &JsonObject) -> Result<Option<T>, String> {
+    match get_json_by_key(connect_string, T::object_name(), key)? {
+        Some(object) => decode_object(object).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn get_by_id<T: ConfigObject>(connect_string: &str, object_id: &str) -> Result<Option<T>, String> {
+    match get_json_by_id(connect_string, T::object_name(), object_id)? {
+        Some(object) => decode_object(object).map(Some),
+        None => Ok(None),
+    }
+}
+
+// ObjectId of the object with the same key fields as the given one
+pub fn get_object_id<T: ConfigObject>(connect_string: &str, object: &T) -> Result<Option<String>, String> {
+    get_json_object_id(connect_string, T::object_name(), &encode_key(object)?)
+}
+
+pub fn get_all<T: ConfigObject>(connect_string: &str) -> Result<Vec<T>, String> {
+    let mut result = vec![];
+
+    for object in get_all_json(connect_string, T::object_name())? {
+        result.push(decode_object(object)?);
+    }
+
+    Ok(result)
+}
+
+pub fn update<T: ConfigObject>(connect_string: &str, object: &T) -> Result<(), String> {
+    update_json(connect_string, T::object_name(), &encode_object(object)?)
+}
+
+pub fn update_by_id<T: ConfigObject>(connect_string: &str, object_id: &str, object: &T) -> Result<(), String> {
+    update_json_by_id(connect_string, T::object_name(), object_id, &encode_object(object)?)
+}
+
+// only the key fields are sent, the switch ignores everything else on delete
+pub fn delete<T: ConfigObject>(connect_string: &str, object: &T) -> Result<(), String> {
+    delete_json(connect_string, T::object_name(), &encode_key(object)?)
+}
+
+pub fn delete_by_id<T: ConfigObject>(connect_string: &str, object_id: &str) -> Result<(), String> {
+    delete_json_by_id(connect_string, T::object_name(), object_id)
+}
+
+pub fn create_json(connect_string: &str, object_name: &str, object: &JsonObject) -> Result<String, String> {
+    let body = send_config_request(Method::Post, connect_string, object_name, object)?;
+    let create_response = decode_body::<CreateResponse>(&body)?;
+    match create_response.object_id.is_empty() {
+        true => Err(format!("missing ObjectId in {}", body)),
+        false => Ok(create_response.object_id),
+    }
+}
+
+pub fn get_json_by_key(connect_string: &str, object_name: &str, key: &JsonObject) -> Result<Option<JsonObject>, String> {
+    get_json_envelope(connect_string, object_name, key).map(|envelope| envelope.map(|envelope| envelope.object))
+}
+
+pub fn get_json_by_id(connect_string: &str, object_name: &str, object_id: &str) -> Result<Option<JsonObject>, String> {
+    get_json_envelope(connect_string, &format!("{}/{}", object_name, object_id), &JsonObject::new())
+        .map(|envelope| envelope.map(|envelope| envelope.object))
+}
+
+pub fn get_json_object_id(connect_string: &str, object_name: &str, key: &JsonObject) -> Result<Option<String>, String> {
+    match get_json_envelope(connect_string, object_name, key)? {
+        Some(ref envelope) if envelope.object_id.is_empty() => Err(format!("missing ObjectId for {}", object_name)),
+        Some(envelope) => Ok(Some(envelope.object_id)),
+        None => Ok(None),
+    }
+}
+
+// follows NextMarker until the switch reports no more objects
+pub fn get_all_json(connect_string: &str, object_name: &str) -> Result<Vec<JsonObject>, String> {
+    let mut result = vec![];
+    let mut marker = 0;
+
+    loop {
+        // the bulk url is the plural of the object name, like /public/v1/config/Vlans
+        let path = format!("{}s?CurrentMarker={}", object_name, marker);
+        let body = send_config_request(Method::Get, connect_string, &path, &JsonObject::new())?;
+        let object_list = decode_body::<ObjectList<JsonObject>>(&body)?;
+
+        result.extend(object_list.objects.into_iter().map(|envelope| envelope.object));
+        if !object_list.more_exist || object_list.next_marker == marker {
+            return Ok(result);
+        }
+        marker = object_list.next_marker;
+    }
+}
+
+pub fn update_json(connect_string: &str, object_name: &str, object: &JsonObject) -> Result<(), String> {
+    send_config_request(Method::Patch, connect_string, object_name, object).map(|_| ())
+}
+
+pub fn update_json_by_id(connect_string: &str, object_name: &str, object_id: &str, object: &JsonObject) -> Result<(), String> {
+    send_config_request(Method::Patch, connect_string, &format!("{}/{}", object_name, object_id), object).map(|_| ())
+}
+
+pub fn delete_json(connect_string: &str, object_name: &str, key: &JsonObject) -> Result<(), String> {
+    send_config_request(Method::Delete, connect_string, object_name, key).map(|_| ())
+}
+
+pub fn delete_json_by_id(connect_string: &str, object_name: &str, object_id: &str) -> Result<(), String> {
+    send_config_request(Method::Delete, connect_string, &format!("{}/{}", object_name, object_id), &JsonObject::new())
+        .map(|_| ())
+}
+
+// a missing object is not an error, the switch answers 404 for it
+fn get_json_envelope(connect_string: &str,
+                     path: &str,
+                     key: &JsonObject)
+                     -> Result<Option<ObjectEnvelope<JsonObject>>, String> {
+    match send_config_request(Method::Get, connect_string, path, key) {
+        Ok(body) => decode_body(&body).map(Some),
+        Err(ref err) if err.starts_with("404") => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+// path is the object name, optionally followed by /<ObjectId>
+// errors start with the http status code, like "404 Not Found: ..."
+fn send_config_request(method: Method, connect_string: &str, path: &str, object: &JsonObject) -> Result<String, String> {
+    let data = serde_json::to_string(object).unwrap();
+    let client = Client::new();
+    let address = format!("http://{}/public/v1/config/{}", connect_string, path);
+
+    // hyper drops the body of a GET, but FlexSwitch looks objects up by the key fields in it
+    let method = match method {
+        Method::Get if !object.is_empty() => Method::Extension("GET".to_string()),
+        method => method,
+    };
+
+    let mut response = client.request(method, &address)
+        .body(&data)
+        .header(ContentType::json())
+        .send()
+        .map_err(|err| format!("{}: {}", address, err))?;
+
+    let mut body = String::new();
+    response.read_to_string(&mut body).map_err(|err| format!("{}: {}", address, err))?;
+
+    match response.status {
+        StatusCode::Ok | StatusCode::Created => Ok(body),
+        status => Err(format!("{}: {} {}", status, address, body.trim())),
+    }
+}
+
+fn encode_object<T: ConfigObject>(object: &T) -> Result<JsonObject, String> {
+    match serde_json::to_value(object) {
+        Ok(Value::Object(object)) => Ok(object),
+        Ok(_) => Err(format!("{} does not encode to a json object", T::object_name())),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+pub(super) fn encode_key<T: ConfigObject>(object: &T) -> Result<JsonObject, String> {
+    let object = encode_object(object)?;
+    Ok(object.into_iter().filter(|(field, _)| T::key_fields().contains(&field.as_str())).collect())
+}
+
+fn decode_object<T: ConfigObject>(object: JsonObject) -> Result<T, String> {
+    serde_json::from_value(Value::Object(object)).map_err(|err| format!("invalid {}: {}", T::object_name(), err))
+}
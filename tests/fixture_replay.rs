@@ -0,0 +1,108 @@
+extern crate torc_snaproute_client;
+
+use std::env;
+use std::fs;
+use std::process;
+use std::time::Duration;
+use torc_snaproute_client::api;
+use torc_snaproute_client::api::fixture::{RecordingProxy, ReplaySwitch, read_fixture};
+use torc_snaproute_client::api::mock::MockSwitch;
+
+fn replay(fixture_file: &str) -> ReplaySwitch {
+    ReplaySwitch::start(&format!("tests/fixtures/{}", fixture_file)).unwrap()
+}
+
+#[test]
+fn get_routes_tolerates_missing_and_null_members() {
+    let switch = replay("routes_with_nulls.json");
+
+    let routes = api::get_routes(&switch.connect_string());
+    let routes: Vec<(&str, &str, &str)> = routes.iter()
+        .map(|route| (route.from.as_str(), route.to.as_str(), route.protocol.as_str()))
+        .collect();
+    assert_eq!(routes,
+               [("10.1.0.0/24", "192.168.0.1", "STATIC"),
+                ("10.2.0.0/24", "", "STATIC"),
+                ("10.3.0.0/24", "", ""),
+                ("10.4.0.0/24", "", "BGP")]);
+}
+
+#[test]
+fn get_routes_of_empty_table() {
+    let switch = replay("empty_routes.json");
+
+    assert!(api::get_routes(&switch.connect_string()).is_empty());
+}
+
+#[test]
+fn get_ports_stats_tolerates_null_members() {
+    let switch = replay("ports_with_nulls.json");
+
+    let ports = api::get_ports_stats(&switch.connect_string());
+    let ports: Vec<(i32, bool)> = ports.iter().map(|port| (port.id, port.connected)).collect();
+    assert_eq!(ports, [(1, true), (2, false)]);
+}
+
+#[test]
+fn get_mac_table_tolerates_null_members() {
+    let switch = replay("mac_table_with_nulls.json");
+
+    let entries = api::get_mac_table(&switch.connect_string());
+    let entries: Vec<(&str, i32, i32, bool)> = entries.iter()
+        .map(|entry| (entry.mac_addr.as_str(), entry.vlan_id, entry.port, entry.is_static))
+        .collect();
+    assert_eq!(entries, [("00:11:22:33:44:55", 100, 1, true), ("00:11:22:33:44:66", 0, 2, false)]);
+}
+
+#[test]
+fn get_lldp_neighbors_tolerates_null_members() {
+    let switch = replay("lldp_neighbors_with_nulls.json");
+
+    let neighbors = api::get_lldp_neighbors(&switch.connect_string());
+    let neighbors: Vec<(i32, &str, &str)> = neighbors.iter()
+        .map(|neighbor| (neighbor.port_id, neighbor.intf_ref.as_str(), neighbor.peer_system_name.as_str()))
+        .collect();
+    assert_eq!(neighbors, [(1, "fpPort1", "spine1"), (0, "fpPort2", "")]);
+}
+
+#[test]
+fn wait_until_ready_needs_readable_daemon_states() {
+    let switch = replay("daemon_states_unavailable.json");
+
+    let err = api::wait_until_ready(&switch.connect_string(), Duration::from_millis(500)).unwrap_err();
+    assert!(err.contains("daemon states not available"), "{}", err);
+}
+
+#[test]
+fn recorded_traffic_replays() {
+    let fixture_file = env::temp_dir().join(format!("torc_snaproute_client_{}.json", process::id()));
+    let fixture_file = fixture_file.to_str().unwrap();
+
+    let upstream = MockSwitch::start();
+    let proxy = RecordingProxy::start(&upstream.connect_string(), fixture_file);
+    let object_id = api::add_route(&proxy.connect_string(), "10.1.0.0/24", "192.168.0.1");
+    let routes = api::get_routes(&proxy.connect_string());
+    api::delete_route(&proxy.connect_string(), "10.1.0.0/24");
+    drop(proxy);
+
+    assert_eq!(read_fixture(fixture_file).unwrap().len(), 4);
+    assert!(upstream.config_objects("IPv4Route").is_empty());
+
+    let switch = ReplaySwitch::start(fixture_file).unwrap();
+    assert_eq!(api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1"), object_id);
+
+    let replayed_routes = api::get_routes(&switch.connect_string());
+    assert_eq!(replayed_routes.len(), routes.len());
+    assert_eq!(replayed_routes[0].from, routes[0].from);
+    assert_eq!(replayed_routes[0].to, routes[0].to);
+
+    fs::remove_file(fixture_file).unwrap();
+}
+
+#[test]
+fn unrecorded_requests_fail() {
+    let switch = replay("empty_routes.json");
+
+    assert!(api::get_ports_stats(&switch.connect_string()).is_empty());
+    assert!(api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1").is_none());
+}
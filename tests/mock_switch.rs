@@ -0,0 +1,345 @@
+extern crate serde_json;
+extern crate torc_snaproute_client;
+
+use serde_json::json;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+use torc_snaproute_client::api;
+use torc_snaproute_client::api::JsonObject;
+use torc_snaproute_client::api::mock::MockSwitch;
+
+fn wedge_switch() -> MockSwitch {
+    let switch = MockSwitch::start();
+    switch.add_port("fpPort1", 1);
+    switch.add_port("fpPort2", 2);
+    switch.add_port("fpPort2s1", 3);
+    switch
+}
+
+#[test]
+fn add_route_shows_up_in_get_routes() {
+    let switch = MockSwitch::start();
+
+    let object_id = api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1");
+    assert!(object_id.is_some());
+
+    let routes = api::get_routes(&switch.connect_string());
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0].from, "10.1.0.0/24");
+    assert_eq!(routes[0].to, "192.168.0.1");
+    assert_eq!(routes[0].protocol, "STATIC");
+}
+
+#[test]
+fn add_route_twice_fails() {
+    let switch = MockSwitch::start();
+
+    assert!(api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1").is_some());
+    assert!(api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.2").is_none());
+    assert_eq!(switch.config_objects("IPv4Route").len(), 1);
+}
+
+#[test]
+fn delete_route_by_key_and_by_id() {
+    let switch = MockSwitch::start();
+
+    api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1");
+    let object_id = api::add_route(&switch.connect_string(), "10.2.0.0/16", "192.168.0.1").unwrap();
+
+    api::delete_route(&switch.connect_string(), "10.1.0.0/24");
+    api::delete_route_by_id(&switch.connect_string(), &object_id);
+
+    assert!(api::get_routes(&switch.connect_string()).is_empty());
+}
+
+#[test]
+fn get_routes_follows_next_marker() {
+    let switch = MockSwitch::start();
+    switch.set_page_size(2);
+
+    for subnet in 1..6 {
+        api::add_route(&switch.connect_string(), &format!("10.{}.0.0/24", subnet), "192.168.0.1");
+    }
+
+    let routes = api::get_routes(&switch.connect_string());
+    let from: Vec<&str> = routes.iter().map(|route| route.from.as_str()).collect();
+    assert_eq!(from, ["10.1.0.0/24", "10.2.0.0/24", "10.3.0.0/24", "10.4.0.0/24", "10.5.0.0/24"]);
+}
+
+#[test]
+fn reset_and_initalize_replaces_config() {
+    let switch = wedge_switch();
+    api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1");
+
+    api::reset_and_initalize(&switch.connect_string(), "examples/config.yml");
+
+    assert_eq!(switch.actions(), ["ResetConfig"]);
+    assert!(switch.config_objects("IPv4Route").is_empty());
+
+    let ports = switch.config_objects("Port");
+    assert_eq!(ports.len(), 3);
+    assert_eq!(ports[1]["BreakOutMode"], "4x10");
+
+    let vlans = switch.config_objects("Vlan");
+    assert_eq!(vlans.len(), 1);
+    assert_eq!(vlans[0]["VlanId"], 100);
+
+    assert_eq!(switch.config_objects("IPv4Intf").len(), 2);
+    assert_eq!(switch.config_objects("Acl").len(), 1);
+}
+
+#[test]
+fn reset_config_keeps_default_config() {
+    let switch = wedge_switch();
+    api::add_vlan(&switch.connect_string(), 100, &["fpPort1".to_string()]);
+
+    api::reset_config(&switch.connect_string()).unwrap();
+
+    assert!(switch.config_objects("Vlan").is_empty());
+    assert_eq!(switch.config_objects("Port").len(), 3);
+    assert_eq!(api::get_ports_stats(&switch.connect_string()).len(), 3);
+}
+
+#[cfg(feature = "async-client")]
+#[tokio::test]
+async fn async_add_route_shows_up_in_get_routes() {
+    let switch = MockSwitch::start();
+
+    assert!(api::async_client::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1").await.is_some());
+
+    let routes = api::async_client::get_routes(&switch.connect_string()).await;
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0].to, "192.168.0.1");
+}
+
+#[cfg(feature = "async-client")]
+#[tokio::test]
+async fn async_delete_route_by_key() {
+    let switch = MockSwitch::start();
+    api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1");
+    api::add_route(&switch.connect_string(), "10.2.0.0/24", "192.168.0.1");
+
+    api::async_client::delete_route(&switch.connect_string(), "10.1.0.0/24").await;
+    api::async_client::delete_route(&switch.connect_string(), "10.9.0.0/24").await;
+
+    let routes = api::get_routes(&switch.connect_string());
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0].from, "10.2.0.0/24");
+}
+
+#[cfg(feature = "async-client")]
+#[tokio::test]
+async fn async_reset_and_initalize_replaces_config() {
+    let switch = wedge_switch();
+    api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1");
+
+    api::async_client::reset_and_initalize(&switch.connect_string(), "examples/config.yml").await;
+
+    assert_eq!(switch.actions(), ["ResetConfig"]);
+    assert!(switch.config_objects("IPv4Route").is_empty());
+    assert_eq!(switch.config_objects("Vlan").len(), 1);
+    assert_eq!(switch.config_objects("IPv4Intf").len(), 2);
+}
+
+#[test]
+fn routes_over_one_next_hop_share_a_bfd_session() {
+    let switch = MockSwitch::start();
+
+    api::add_route_with_bfd(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1", "fast").unwrap();
+    api::add_route_with_bfd(&switch.connect_string(), "10.2.0.0/24", "192.168.0.1", "fast").unwrap();
+    assert_eq!(switch.config_objects("BfdSession").len(), 1);
+
+    api::delete_route_with_bfd(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1").unwrap();
+    assert_eq!(switch.config_objects("BfdSession").len(), 1);
+
+    api::delete_route_with_bfd(&switch.connect_string(), "10.2.0.0/24", "192.168.0.1").unwrap();
+    assert!(switch.config_objects("BfdSession").is_empty());
+    assert!(switch.config_objects("IPv4Route").is_empty());
+}
+
+#[test]
+fn failed_route_with_bfd_removes_its_session() {
+    let switch = MockSwitch::start();
+    api::add_route(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1");
+
+    assert!(api::add_route_with_bfd(&switch.connect_string(), "10.1.0.0/24", "192.168.0.1", "fast").is_err());
+    assert!(switch.config_objects("BfdSession").is_empty());
+}
+
+#[test]
+fn add_port_ipv4_interface_rejects_invalid_sub_interface() {
+    let switch = wedge_switch();
+
+    assert!(api::add_port_ipv4_interface(&switch.connect_string(), "fpPort1.abc", "192.168.0.2/30").is_err());
+    assert!(api::add_port_ipv4_interface(&switch.connect_string(), "fpPort9", "192.168.0.2/30").is_err());
+    assert!(api::add_port_ipv4_interface(&switch.connect_string(), "fpPort1.200", "192.168.0.2/30").is_ok());
+    assert_eq!(switch.config_objects("SubIPv4Intf").len(), 1);
+}
+
+#[test]
+fn bulk_routes_report_each_result() {
+    let switch = MockSwitch::start();
+
+    let routes = vec![("10.1.0.0/24", "192.168.0.1"), ("10.2.0.0/abc", "192.168.0.1"), ("10.3.0.0/24", "192.168.0.2")];
+    let added = api::add_routes(&switch.connect_string(), routes, 2);
+    let from: Vec<&str> = added.iter().map(|route| route.from.as_str()).collect();
+    assert_eq!(from, ["10.1.0.0/24", "10.2.0.0/abc", "10.3.0.0/24"]);
+    assert!(added[0].result.as_ref().unwrap().is_some());
+    assert!(added[1].result.is_err());
+    assert!(added[2].result.as_ref().unwrap().is_some());
+    assert_eq!(switch.config_objects("IPv4Route").len(), 2);
+
+    let deleted = api::delete_routes(&switch.connect_string(), vec!["10.1.0.0/24", "10.2.0.0/abc", "10.3.0.0/24"], 2);
+    assert_eq!(deleted[0].result, Ok(None));
+    assert!(deleted[1].result.is_err());
+    assert_eq!(deleted[2].result, Ok(None));
+    assert!(switch.config_objects("IPv4Route").is_empty());
+}
+
+#[test]
+fn wait_until_ready_once_system_and_daemons_are_up() {
+    let switch = MockSwitch::start();
+    switch.set_state_object("SystemStatus", &json!({"Name": "wedge", "Ready": true}));
+    switch.add_state_object("DaemonStates", &json!({"Name": "ribd", "Enable": true, "State": "up"}));
+    switch.add_state_object("DaemonStates", &json!({"Name": "vrrpd", "Enable": false, "State": "down"}));
+
+    assert!(api::wait_until_ready(&switch.connect_string(), Duration::from_secs(5)).is_ok());
+}
+
+#[test]
+fn wait_until_ready_times_out_while_system_is_not_ready() {
+    let switch = MockSwitch::start();
+    switch.set_state_object("SystemStatus", &json!({"Ready": false, "Reason": "booting"}));
+
+    let started = Instant::now();
+    let err = api::wait_until_ready(&switch.connect_string(), Duration::from_millis(1500)).unwrap_err();
+    assert!(err.contains("system not ready: booting"), "{}", err);
+    assert!(started.elapsed() >= Duration::from_millis(1500));
+}
+
+#[test]
+fn wait_until_ready_reports_a_daemon_that_is_down() {
+    let switch = MockSwitch::start();
+    switch.set_state_object("SystemStatus", &json!({"Ready": true}));
+    switch.add_state_object("DaemonStates", &json!({"Name": "bgpd", "Enable": true, "State": "restarting"}));
+
+    let err = api::wait_until_ready(&switch.connect_string(), Duration::from_millis(500)).unwrap_err();
+    assert!(err.contains("daemon bgpd is restarting"), "{}", err);
+}
+
+#[test]
+fn wait_until_ready_gives_up_on_a_switch_that_never_answers() {
+    // connections are accepted by the kernel but never read
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    let started = Instant::now();
+    let err = api::wait_until_ready(&listener.local_addr().unwrap().to_string(), Duration::from_secs(1)).unwrap_err();
+    assert!(err.contains("system status not available"), "{}", err);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn add_route_fails_on_a_truncated_response() {
+    // promises a longer body than it sends, then closes the connection
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let connect_string = listener.local_addr().unwrap().to_string();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = BufReader::new(stream.try_clone().unwrap());
+        let mut content_length = 0;
+        let mut line = String::new();
+        while request.read_line(&mut line).unwrap() > 2 {
+            if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                content_length = length.trim().parse().unwrap();
+            }
+            line.clear();
+        }
+        request.read_exact(&mut vec![0; content_length]).unwrap();
+
+        stream.write_all(b"HTTP/1.1 201 Created\r\nContent-Length: 100\r\n\r\n{\"ObjectId\": ").unwrap();
+    });
+
+    assert!(api::add_route(&connect_string, "10.1.0.0/24", "192.168.0.1").is_none());
+    server.join().unwrap();
+}
+
+fn json_object(value: serde_json::Value) -> JsonObject {
+    value.as_object().unwrap().clone()
+}
+
+#[test]
+fn typed_config_object_round_trip() {
+    let switch = MockSwitch::start();
+    let connect_string = switch.connect_string();
+    let vlan = |vlan_id, ports: &[&str]| {
+        api::Vlan {
+            VlanId: vlan_id,
+            UntagIntfList: ports.iter().map(|port| port.to_string()).collect(),
+        }
+    };
+
+    let object_id = api::create(&connect_string, &vlan(100, &["fpPort1"])).unwrap();
+    assert!(api::create(&connect_string, &vlan(100, &["fpPort1"])).is_err());
+
+    let found: api::Vlan = api::get_by_key(&connect_string, &json_object(json!({"VlanId": 100}))).unwrap().unwrap();
+    assert_eq!(found.UntagIntfList, ["fpPort1"]);
+    assert_eq!(api::get_object_id(&connect_string, &vlan(100, &[])).unwrap(), Some(object_id));
+
+    api::update(&connect_string, &vlan(100, &["fpPort1", "fpPort2"])).unwrap();
+    assert_eq!(api::get(&connect_string, &vlan(100, &[])).unwrap().unwrap().UntagIntfList.len(), 2);
+
+    let other_id = api::create(&connect_string, &vlan(200, &[])).unwrap();
+    api::update_by_id(&connect_string, &other_id, &vlan(200, &["fpPort3"])).unwrap();
+    let other: api::Vlan = api::get_by_id(&connect_string, &other_id).unwrap().unwrap();
+    assert_eq!(other.UntagIntfList, ["fpPort3"]);
+
+    let vlans: Vec<api::Vlan> = api::get_all(&connect_string).unwrap();
+    let vlan_ids: Vec<i32> = vlans.iter().map(|vlan| vlan.VlanId).collect();
+    assert_eq!(vlan_ids, [100, 200]);
+
+    api::delete(&connect_string, &vlan(100, &[])).unwrap();
+    api::delete_by_id::<api::Vlan>(&connect_string, &other_id).unwrap();
+    assert!(api::get(&connect_string, &vlan(100, &[])).unwrap().is_none());
+    assert!(api::get_all::<api::Vlan>(&connect_string).unwrap().is_empty());
+}
+
+#[test]
+fn json_objects_without_bindings() {
+    let switch = MockSwitch::start();
+    let connect_string = switch.connect_string();
+    let neighbor = |address: &str, peer_as: i64| json_object(json!({"NeighborAddress": address, "PeerAS": peer_as}));
+
+    let first_id = api::create_json(&connect_string, "BGPv4Neighbor", &neighbor("10.0.0.1", 65001)).unwrap();
+    let second_id = api::create_json(&connect_string, "BGPv4Neighbor", &neighbor("10.0.0.2", 65002)).unwrap();
+    assert_ne!(first_id, second_id);
+
+    api::update_json_by_id(&connect_string, "BGPv4Neighbor", &first_id, &neighbor("10.0.0.1", 65003)).unwrap();
+    let first = api::get_json_by_id(&connect_string, "BGPv4Neighbor", &first_id).unwrap().unwrap();
+    assert_eq!(first["PeerAS"], 65003);
+    assert_eq!(api::get_all_json(&connect_string, "BGPv4Neighbor").unwrap().len(), 2);
+
+    api::delete_json_by_id(&connect_string, "BGPv4Neighbor", &second_id).unwrap();
+    assert!(api::get_json_by_id(&connect_string, "BGPv4Neighbor", &second_id).unwrap().is_none());
+    assert_eq!(api::get_all_json(&connect_string, "BGPv4Neighbor").unwrap().len(), 1);
+}
+
+#[test]
+fn json_objects_by_key() {
+    let switch = MockSwitch::start();
+    switch.set_key_fields("BGPv4Neighbor", &["NeighborAddress"]);
+    let connect_string = switch.connect_string();
+    let key = json_object(json!({"NeighborAddress": "10.0.0.1"}));
+    let neighbor = |peer_as: i64| json_object(json!({"NeighborAddress": "10.0.0.1", "PeerAS": peer_as}));
+
+    let object_id = api::create_json(&connect_string, "BGPv4Neighbor", &neighbor(65001)).unwrap();
+    assert!(api::create_json(&connect_string, "BGPv4Neighbor", &neighbor(65001)).is_err());
+    assert_eq!(api::get_json_object_id(&connect_string, "BGPv4Neighbor", &key).unwrap(), Some(object_id));
+
+    api::update_json(&connect_string, "BGPv4Neighbor", &neighbor(65002)).unwrap();
+    assert_eq!(api::get_json_by_key(&connect_string, "BGPv4Neighbor", &key).unwrap().unwrap()["PeerAS"], 65002);
+
+    api::delete_json(&connect_string, "BGPv4Neighbor", &key).unwrap();
+    assert!(api::get_json_by_key(&connect_string, "BGPv4Neighbor", &key).unwrap().is_none());
+}
@@ -0,0 +1,19 @@
+extern crate torc_snaproute_client;
+
+use torc_snaproute_client::api::models::{IPv4Route, NextHopInfo, Port};
+use torc_snaproute_client::api::ConfigObject;
+
+#[test]
+fn generated_models_have_keys_and_defaults() {
+    let route = IPv4Route::default();
+    assert_eq!(route.Protocol, "STATIC");
+    assert!(route.NextHop.is_empty());
+    assert_eq!(IPv4Route::object_name(), "IPv4Route");
+    assert_eq!(IPv4Route::key_fields(), ["DestinationNw", "NetworkMask"]);
+
+    let port = Port { IntfRef: "fpPort1".to_string(), ..Default::default() };
+    assert!(port.Speed.is_none());
+    assert_eq!(Port::key_fields(), ["IntfRef"]);
+
+    assert_eq!(NextHopInfo::default().NextHopIp, "");
+}